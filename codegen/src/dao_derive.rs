@@ -59,6 +59,21 @@ pub fn impl_to_dao(ast: &syn::MacroInput) -> quote::Tokens {
             quote! { dao.insert(stringify!(#field), &self.#field);}
         })
         .collect();
+    let try_from_fields: &Vec<quote::Tokens> = &fields
+        .iter()
+        .map(|&(field, ty)| {
+            if is_fallible(ty) {
+                quote! {
+                    dao.insert_value(
+                        stringify!(#field),
+                        &wumn_dao::TryToValue::try_to_value(&self.#field)?,
+                    );
+                }
+            } else {
+                quote! { dao.insert(stringify!(#field), &self.#field);}
+            }
+        })
+        .collect();
 
     quote! {
         impl wumn_dao::ToDao for  #name {
@@ -68,10 +83,36 @@ pub fn impl_to_dao(ast: &syn::MacroInput) -> quote::Tokens {
                 #(#from_fields)*
                 dao
             }
+
+            fn try_to_dao(&self) -> Result<wumn_dao::Dao, wumn_dao::ConvertError> {
+                let mut dao = wumn_dao::Dao::new();
+                #(#try_from_fields)*
+                Ok(dao)
+            }
         }
 
     }
 }
 
+/// whether the field can fail to convert, converted with `TryToValue` when it is saved.
+/// These are the `Json` and the dates of the time crate, or their `Option`.
+fn is_fallible(ty: &syn::Ty) -> bool {
+    match *ty {
+        syn::Ty::Path(None, ref path) => match path.segments.last() {
+            Some(segment) => match (segment.ident.as_ref(), &segment.parameters) {
+                ("Json", _) | ("OffsetDateTime", _) | ("PrimitiveDateTime", _) => true,
+                // not the generic Date of chrono
+                ("Date", params) => params.is_empty(),
+                ("Option", &syn::PathParameters::AngleBracketed(ref data)) => {
+                    data.types.len() == 1 && is_fallible(&data.types[0])
+                }
+                _ => false,
+            },
+            None => false,
+        },
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {}
@@ -9,6 +9,8 @@ mod column_derive;
 #[macro_use]
 mod dao_derive;
 #[macro_use]
+mod value_derive;
+#[macro_use]
 mod table_derive;
 
 use proc_macro::TokenStream;
@@ -44,3 +46,19 @@ pub fn to_column_names(input: TokenStream) -> TokenStream {
     let gen = column_derive::impl_to_column_names(&ast);
     gen.parse().unwrap()
 }
+
+#[proc_macro_derive(DbEnum, attributes(wumn))]
+pub fn db_enum(input: TokenStream) -> TokenStream {
+    let s = input.to_string();
+    let ast = syn::parse_macro_input(&s).unwrap();
+    let gen = value_derive::impl_db_enum(&ast);
+    gen.parse().unwrap()
+}
+
+#[proc_macro_derive(DbComposite)]
+pub fn db_composite(input: TokenStream) -> TokenStream {
+    let s = input.to_string();
+    let ast = syn::parse_macro_input(&s).unwrap();
+    let gen = value_derive::impl_db_composite(&ast);
+    gen.parse().unwrap()
+}
@@ -0,0 +1,187 @@
+use quote;
+use syn;
+
+/// the string of `#[wumn(key = "value")]` in these attributes
+fn wumn_attr(attrs: &[syn::Attribute], key: &str) -> Option<String> {
+    attrs
+        .iter()
+        .filter_map(|attr| match attr.value {
+            syn::MetaItem::List(ref ident, ref items) if ident == "wumn" => Some(items),
+            _ => None,
+        })
+        .flat_map(|items| items.iter())
+        .filter_map(|item| match *item {
+            syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(
+                ref ident,
+                syn::Lit::Str(ref value, _),
+            )) if ident == key => Some(value.to_owned()),
+            _ => None,
+        })
+        .next()
+}
+
+/// the snake_case of a CamelCase rust name, ie: `MpaaRating` is `mpaa_rating`,
+/// used for the enum name and its variants
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+pub fn impl_db_enum(ast: &syn::MacroInput) -> quote::Tokens {
+    let name = &ast.ident;
+    let enum_name =
+        wumn_attr(&ast.attrs, "name").unwrap_or_else(|| snake_case(&name.to_string()));
+    let variants: Vec<(&syn::Ident, String)> = match ast.body {
+        syn::Body::Enum(ref variants) => variants
+            .iter()
+            .map(|v| match v.data {
+                syn::VariantData::Unit => {
+                    let choice = wumn_attr(&v.attrs, "rename")
+                        .unwrap_or_else(|| snake_case(&v.ident.to_string()));
+                    (&v.ident, choice)
+                }
+                _ => panic!("Only unit variants are supported for #[derive(DbEnum)]"),
+            })
+            .collect::<Vec<_>>(),
+        syn::Body::Struct(_) => panic!("#[derive(DbEnum)] can only be used with enums"),
+    };
+    let choices: &Vec<&str> = &variants.iter().map(|(_, choice)| choice.as_str()).collect();
+    let to_choices: Vec<quote::Tokens> = variants
+        .iter()
+        .map(|(variant, choice)| {
+            let choice = choice.as_str();
+            quote! { #name::#variant => #choice, }
+        })
+        .collect();
+    let from_choices: Vec<quote::Tokens> = variants
+        .iter()
+        .map(|(variant, choice)| {
+            let choice = choice.as_str();
+            quote! { #choice => Some(#name::#variant), }
+        })
+        .collect();
+    let value_conversions = value_conversions(name);
+
+    quote! {
+        impl wumn_dao::DbEnum for #name {
+
+            fn enum_name() -> &'static str {
+                #enum_name
+            }
+
+            fn choices() -> &'static [&'static str] {
+                &[#(#choices),*]
+            }
+
+            fn to_choice(&self) -> &'static str {
+                match *self {
+                    #(#to_choices)*
+                }
+            }
+
+            fn from_choice(choice: &str) -> Option<Self> {
+                match choice {
+                    #(#from_choices)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl wumn_dao::DbValue for #name {
+            fn to_db_value(&self) -> wumn_dao::Value {
+                wumn_dao::Value::Text(wumn_dao::DbEnum::to_choice(self).to_string())
+            }
+
+            fn from_db_value(value: &wumn_dao::Value) -> Result<Self, wumn_dao::ConvertError> {
+                wumn_dao::DbEnum::from_value(value)
+            }
+        }
+
+        #value_conversions
+    }
+}
+
+/// a struct with ToDao stored as a composite type of the database,
+/// its fields are read with an error instead of the panic of FromDao
+pub fn impl_db_composite(ast: &syn::MacroInput) -> quote::Tokens {
+    let name = &ast.ident;
+    let fields: Vec<&syn::Ident> = match ast.body {
+        syn::Body::Struct(syn::VariantData::Struct(ref fields)) => {
+            fields.iter().map(|f| f.ident.as_ref().unwrap()).collect()
+        }
+        _ => panic!("#[derive(DbComposite)] can only be used with structs with named fields"),
+    };
+    let from_fields: Vec<quote::Tokens> = fields
+        .iter()
+        .map(|field| {
+            quote! {
+                #field: dao.get(stringify!(#field)).map_err(|e| {
+                    let target = concat!(stringify!(#name), ".", stringify!(#field)).to_string();
+                    match e {
+                        wumn_dao::DaoError::ConvertError(e) => {
+                            wumn_dao::ConvertError::NotSupported(format!("{:?}", e), target)
+                        }
+                        wumn_dao::DaoError::NoSuchValueError(field) => {
+                            wumn_dao::ConvertError::NotSupported(format!("missing {}", field), target)
+                        }
+                    }
+                })?,
+            }
+        })
+        .collect();
+    let value_conversions = value_conversions(name);
+
+    quote! {
+        impl wumn_dao::DbValue for #name {
+            fn to_db_value(&self) -> wumn_dao::Value {
+                wumn_dao::Value::from(wumn_dao::ToDao::to_dao(self))
+            }
+
+            fn from_db_value(value: &wumn_dao::Value) -> Result<Self, wumn_dao::ConvertError> {
+                let dao: wumn_dao::Dao = std::convert::TryFrom::try_from(value)?;
+                Ok(#name {
+                    #(#from_fields)*
+                })
+            }
+        }
+
+        #value_conversions
+    }
+}
+
+/// the conversions from and to a value through DbValue
+fn value_conversions(name: &syn::Ident) -> quote::Tokens {
+    quote! {
+        impl wumn_dao::ToValue for #name {
+            fn to_value(&self) -> wumn_dao::Value {
+                wumn_dao::DbValue::to_db_value(self)
+            }
+        }
+
+        impl From<#name> for wumn_dao::Value {
+            fn from(f: #name) -> Self {
+                wumn_dao::DbValue::to_db_value(&f)
+            }
+        }
+
+        impl<'a> From<&'a #name> for wumn_dao::Value {
+            fn from(f: &'a #name) -> Self {
+                wumn_dao::DbValue::to_db_value(f)
+            }
+        }
+
+        impl<'a> std::convert::TryFrom<&'a wumn_dao::Value> for #name {
+            type Error = wumn_dao::ConvertError;
+
+            fn try_from(value: &'a wumn_dao::Value) -> Result<Self, Self::Error> {
+                wumn_dao::DbValue::from_db_value(value)
+            }
+        }
+    }
+}
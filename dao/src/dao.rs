@@ -2,7 +2,7 @@ use serde::{Serialize, Serializer, Deserialize, Deserializer};
 use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::fmt::Debug;
-use crate::error::DaoError;
+use crate::error::{ConvertError, DaoError};
 use crate::Value;
 
 #[derive(Debug, PartialEq, Clone, Default)]
@@ -67,6 +67,33 @@ impl<'de> Deserialize<'de> for Dao {
     }
 }
 
+/// a dao is a composite value, its fields ordered by name
+impl From<Dao> for Value {
+    fn from(dao: Dao) -> Self {
+        Value::Composite(dao.0.into_iter().collect())
+    }
+}
+
+impl<'a> From<&'a Dao> for Value {
+    fn from(dao: &'a Dao) -> Self {
+        From::from(dao.to_owned())
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for Dao {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Composite(ref fields) => Ok(Dao(fields.iter().cloned().collect())),
+            _ => Err(ConvertError::NotSupported(
+                format!("{:?}", value),
+                "Dao".into(),
+            )),
+        }
+    }
+}
+
 pub trait FromDao {
     /// convert dao to an instance of the corresponding struct of the model
     /// taking into considerating the renamed columns
@@ -77,6 +104,12 @@ pub trait ToDao {
     /// convert from an instance of the struct to a dao representation
     /// to be saved into the database
     fn to_dao(&self) -> Dao;
+
+    /// like `to_dao` but a field that can not be converted is an error instead of a null,
+    /// this is what the entity manager saves
+    fn try_to_dao(&self) -> Result<Dao, ConvertError> {
+        Ok(self.to_dao())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,90 @@
+//! A rust enum stored in an enum type of the database, ie: `mpaa_rating`.
+//! The values are read and written as the text of their choice.
+use crate::error::ConvertError;
+use crate::Value;
+
+/// Derived with `#[derive(DbEnum)]`, each variant is one choice of the enum in the database.
+/// A choice is the snake_case of its variant, ie: `InProgress` is `in_progress`,
+/// unless renamed with `#[wumn(rename = "...")]`
+pub trait DbEnum: Sized {
+    /// the name of the enum type in the database
+    fn enum_name() -> &'static str;
+
+    /// the choices of the enum in the order of the variants
+    fn choices() -> &'static [&'static str];
+
+    fn to_choice(&self) -> &'static str;
+
+    fn from_choice(choice: &str) -> Option<Self>;
+
+    fn from_value(value: &Value) -> Result<Self, ConvertError> {
+        let choice = match *value {
+            Value::Text(ref v) => Self::from_choice(v),
+            _ => None,
+        };
+        choice.ok_or_else(|| {
+            ConvertError::NotSupported(format!("{:?}", value), Self::enum_name().into())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::DbValue;
+    use std::convert::TryFrom;
+
+    #[derive(Debug, PartialEq)]
+    enum Rating {
+        G,
+        Pg13,
+    }
+
+    impl DbEnum for Rating {
+        fn enum_name() -> &'static str {
+            "mpaa_rating"
+        }
+
+        fn choices() -> &'static [&'static str] {
+            &["G", "PG-13"]
+        }
+
+        fn to_choice(&self) -> &'static str {
+            match *self {
+                Rating::G => "G",
+                Rating::Pg13 => "PG-13",
+            }
+        }
+
+        fn from_choice(choice: &str) -> Option<Self> {
+            match choice {
+                "G" => Some(Rating::G),
+                "PG-13" => Some(Rating::Pg13),
+                _ => None,
+            }
+        }
+    }
+
+    impl DbValue for Rating {
+        fn to_db_value(&self) -> Value {
+            Value::Text(self.to_choice().to_string())
+        }
+
+        fn from_db_value(value: &Value) -> Result<Self, ConvertError> {
+            Self::from_value(value)
+        }
+    }
+
+    #[test]
+    fn enum_choices() {
+        assert_eq!(Rating::from_value(&Value::Text("PG-13".into())).unwrap(), Rating::Pg13);
+        assert!(Rating::from_value(&Value::Text("pg-13".into())).is_err());
+        assert!(Rating::from_value(&Value::Int(1)).is_err());
+        let rating: Option<Rating> = TryFrom::try_from(&Value::Nil).unwrap();
+        assert_eq!(rating, None);
+        let rating: Option<Rating> = TryFrom::try_from(&Value::Text("G".into())).unwrap();
+        assert_eq!(rating, Some(Rating::G));
+        assert_eq!(Value::from(&Some(Rating::G)), Value::Text("G".into()));
+        assert_eq!(Value::from(&None::<Rating>), Value::Nil);
+    }
+}
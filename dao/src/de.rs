@@ -0,0 +1,357 @@
+//! Deserialize any serde type from a Dao or a Value, without the FromDao derive.
+//! The dao is read as a map of its column names, the json values are deserialized as is.
+use serde::de::{
+    self, value::MapDeserializer, Deserialize, Deserializer, IntoDeserializer, Visitor,
+};
+use crate::error::ConvertError;
+use crate::export::to_json;
+use crate::value::Array;
+use crate::{Dao, Value};
+
+/// deserialize the dao into T, like FromDao but for any type that implements Deserialize
+/// ```rust
+/// use serde::Deserialize;
+/// use wumn_dao::{from_dao, Dao};
+///
+/// #[derive(Deserialize)]
+/// struct Actor {
+///     actor_id: i32,
+///     #[serde(rename = "first_name")]
+///     name: String,
+///     nickname: Option<String>,
+/// }
+///
+/// let mut dao = Dao::new();
+/// dao.insert("actor_id", 1);
+/// dao.insert("first_name", "PENELOPE");
+/// let actor: Actor = from_dao(&dao).unwrap();
+/// assert_eq!(actor.name, "PENELOPE");
+/// assert_eq!(actor.nickname, None);
+/// ```
+pub fn from_dao<'a, T>(dao: &'a Dao) -> Result<T, ConvertError>
+where
+    T: Deserialize<'a>,
+{
+    T::deserialize(DaoDeserializer(dao))
+}
+
+/// deserialize a single value into T
+pub fn from_value<'a, T>(value: &'a Value) -> Result<T, ConvertError>
+where
+    T: Deserialize<'a>,
+{
+    T::deserialize(ValueDeserializer(value))
+}
+
+impl de::Error for ConvertError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ConvertError::SerdeError(msg.to_string())
+    }
+}
+
+struct DaoDeserializer<'de>(&'de Dao);
+
+impl<'de> Deserializer<'de> for DaoDeserializer<'de> {
+    type Error = ConvertError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let entries = self
+            .0
+             .0
+            .iter()
+            .map(|(k, v)| (k.as_str(), ValueDeserializer(v)));
+        let mut map = MapDeserializer::new(entries);
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(value)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct ValueDeserializer<'de>(&'de Value);
+
+impl<'de> IntoDeserializer<'de, ConvertError> for ValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// deserialize through the json representation of the value,
+/// used for the values that have no direct equivalent in the serde data model
+fn deserialize_as_json<'de, V, T>(value: &T, visitor: V) -> Result<V::Value, ConvertError>
+where
+    V: Visitor<'de>,
+    T: serde::Serialize,
+{
+    let json = serde_json::to_value(value).map_err(ConvertError::JsonError)?;
+    json.deserialize_any(visitor).map_err(ConvertError::JsonError)
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = ConvertError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.0 {
+            Value::Nil => visitor.visit_unit(),
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::Tinyint(v) => visitor.visit_i8(v),
+            Value::Smallint(v) => visitor.visit_i16(v),
+            Value::Int(v) => visitor.visit_i32(v),
+            Value::Bigint(v) => visitor.visit_i64(v),
+            Value::Float(v) => visitor.visit_f32(v),
+            Value::Double(v) => visitor.visit_f64(v),
+            Value::BigDecimal(ref v) => visitor.visit_string(v.to_string()),
+            Value::Blob(ref v) => {
+                visitor.visit_seq(de::value::SeqDeserializer::new(v.iter().cloned()))
+            }
+            Value::ImageUri(ref v) => visitor.visit_borrowed_str(v),
+            Value::Char(v) => visitor.visit_char(v),
+            Value::Text(ref v) => visitor.visit_borrowed_str(v),
+            Value::Json(ref v) => v.deserialize_any(visitor).map_err(ConvertError::JsonError),
+            Value::Uuid(ref v) => visitor.visit_string(v.to_string()),
+            Value::Date(ref v) => visitor.visit_string(v.to_string()),
+            Value::Time(ref v) => visitor.visit_string(v.to_string()),
+            Value::TimeTz(ref v) => visitor.visit_string(v.to_string()),
+            Value::Inet(ref v) => visitor.visit_string(v.to_string()),
+            Value::Cidr(ref v) => visitor.visit_string(format!("{}/{}", v.addr, v.prefix)),
+            Value::MacAddr(ref v) => visitor.visit_string(v.to_string()),
+            Value::DateTime(ref v) => {
+                visitor.visit_string(v.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+            }
+            Value::Timestamp(ref v) => visitor.visit_string(v.to_rfc3339()),
+            Value::Interval(ref v) => deserialize_as_json(v, visitor),
+            Value::Point(ref v) => deserialize_as_json(v, visitor),
+            Value::Line(ref v) => deserialize_as_json(v, visitor),
+            Value::LineSegment(ref v) => deserialize_as_json(v, visitor),
+            Value::Box(ref v) => deserialize_as_json(v, visitor),
+            Value::Path(ref v) => deserialize_as_json(v, visitor),
+            Value::Polygon(ref v) => deserialize_as_json(v, visitor),
+            Value::Circle(ref v) => deserialize_as_json(v, visitor),
+            Value::Geometry(ref v) => deserialize_as_json(v, visitor),
+            Value::Array(Array::Int(ref v)) => deserialize_as_json(v, visitor),
+            Value::Array(Array::Float(ref v)) => deserialize_as_json(v, visitor),
+            Value::Array(Array::Text(ref v)) => deserialize_as_json(v, visitor),
+            Value::Range(_) | Value::Multirange(_) => {
+                to_json(self.0).deserialize_any(visitor).map_err(ConvertError::JsonError)
+            }
+            Value::Composite(ref fields) => {
+                let entries = fields.iter().map(|(k, v)| (k.as_str(), ValueDeserializer(v)));
+                let mut map = MapDeserializer::new(entries);
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.0 {
+            Value::Nil | Value::Json(serde_json::Value::Null) => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_f64(visitor)
+    }
+
+    /// numeric values are read as floats when a float is expected
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.0 {
+            Value::BigDecimal(ref v) => match v.to_string().parse::<f64>() {
+                Ok(v) => visitor.visit_f64(v),
+                Err(e) => Err(ConvertError::SerdeError(e.to_string())),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.0 {
+            Value::Blob(ref v) => visitor.visit_borrowed_bytes(v),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// unit variants are stored as text, the others as json
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match *self.0 {
+            Value::Text(ref v) => visitor.visit_enum(v.as_str().into_deserializer()),
+            Value::Json(ref v) => v
+                .deserialize_enum(name, variants, visitor)
+                .map_err(ConvertError::JsonError),
+            _ => Err(ConvertError::NotSupported(
+                format!("{:?}", self.0),
+                name.to_string(),
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string
+        unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+    use std::convert::TryFrom;
+    use uuid::Uuid;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Rating {
+        G,
+        Pg,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Audit {
+        last_update: DateTime<Utc>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Film {
+        film_id: i64,
+        title: String,
+        rating: Rating,
+        release_date: NaiveDate,
+        uid: Uuid,
+        tags: Vec<String>,
+        rental_rate: f64,
+        extra: BTreeMap<String, i32>,
+        #[serde(flatten)]
+        audit: Audit,
+    }
+
+    #[test]
+    fn deserialize_dao() {
+        let mut dao = Dao::new();
+        dao.insert("film_id", 1);
+        dao.insert("title", "ACADEMY DINOSAUR");
+        dao.insert("rating", "pg");
+        dao.insert("release_date", NaiveDate::from_ymd_opt(2006, 2, 15).unwrap());
+        dao.insert("uid", Uuid::nil());
+        dao.insert("tags", vec!["Trailers".to_string()]);
+        dao.insert("rental_rate", 0.99f32);
+        dao.insert("extra", serde_json::json!({"copies": 3}));
+        let last_update = Utc.with_ymd_and_hms(2006, 2, 15, 9, 58, 20).unwrap();
+        dao.insert("last_update", last_update);
+        let film: Film = from_dao(&dao).unwrap();
+        assert_eq!(film.film_id, 1);
+        assert_eq!(film.rating, Rating::Pg);
+        assert_eq!(film.release_date, NaiveDate::from_ymd_opt(2006, 2, 15).unwrap());
+        assert_eq!(film.uid, Uuid::nil());
+        assert_eq!(film.tags, vec!["Trailers".to_string()]);
+        assert!((film.rental_rate - 0.99).abs() < 1e-6);
+        assert_eq!(film.extra.get("copies"), Some(&3));
+        assert_eq!(film.audit.last_update, last_update);
+    }
+
+    #[test]
+    fn deserialize_missing_and_invalid() {
+        #[derive(Debug, Deserialize)]
+        struct Actor {
+            #[allow(dead_code)]
+            actor_id: i32,
+        }
+        let dao = Dao::new();
+        let actor: Result<Actor, _> = from_dao(&dao);
+        assert!(actor.is_err());
+        let value = Value::Text("not a number".into());
+        let number: Result<i32, _> = from_value(&value);
+        assert!(number.is_err());
+        let nil: Option<i32> = from_value(&Value::Nil).unwrap();
+        assert_eq!(nil, None);
+    }
+
+    #[test]
+    fn deserialize_composite() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Address {
+            street: String,
+            zip: Option<i32>,
+        }
+        let value = Value::Composite(vec![
+            ("street".into(), Value::Text("47 MySakila Drive".into())),
+            ("zip".into(), Value::Nil),
+        ]);
+        let address: Address = from_value(&value).unwrap();
+        assert_eq!(
+            address,
+            Address {
+                street: "47 MySakila Drive".into(),
+                zip: None
+            }
+        );
+        let dao = Dao::try_from(&value).unwrap();
+        assert_eq!(Value::from(dao), value);
+    }
+}
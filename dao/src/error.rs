@@ -7,6 +7,9 @@ use std::fmt::Debug;
 #[derive(Debug)]
 pub enum ConvertError {
     NotSupported(String, String),
+    JsonError(serde_json::Error),
+    /// raised by the serde serializer and deserializer of Dao and Value
+    SerdeError(String),
 }
 
 impl Error for ConvertError {
@@ -17,7 +20,13 @@ impl Error for ConvertError {
 
 impl fmt::Display for ConvertError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self)
+        match *self {
+            ConvertError::NotSupported(ref from, ref to) => {
+                write!(f, "unable to convert {} to {}", from, to)
+            }
+            ConvertError::JsonError(ref e) => write!(f, "{}", e),
+            ConvertError::SerdeError(ref msg) => write!(f, "{}", msg),
+        }
     }
 }
 
@@ -0,0 +1,412 @@
+//! Writers of rows in csv and in newline delimited json, for the consumers outside of rust.
+//! The writers take the rows one at a time, so a large result can be written
+//! as it is being retrieved, without collecting it first.
+use serde_json::json;
+use std::io::{self, Write};
+use crate::value::Array;
+use crate::{Range, Rows, Value};
+use std::ops::Bound;
+
+/// the format of `Value::DateTime`, which has no timezone
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+
+impl Rows {
+    /// write the rows in csv, with the column names as the header
+    /// ```rust
+    /// use wumn_dao::{Rows, Value};
+    ///
+    /// let mut rows = Rows::new(vec!["actor_id".into(), "first_name".into()]);
+    /// rows.push(vec![Value::Int(1), Value::Text("PENELOPE, \"PEN\"".into())]);
+    /// rows.push(vec![Value::Int(2), Value::Nil]);
+    /// let mut csv = vec![];
+    /// rows.write_csv(&mut csv).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(csv).unwrap(),
+    ///     "actor_id,first_name\n1,\"PENELOPE, \"\"PEN\"\"\"\n2,\n"
+    /// );
+    /// ```
+    pub fn write_csv<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut csv = CsvWriter::new(writer, self.columns.clone());
+        csv.write_rows(self)?;
+        csv.finish().map(|_| ())
+    }
+
+    /// write each row as a json object on its own line,
+    /// the values are plain json and not the tagged form of the serialized `Value`
+    pub fn write_ndjson<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut ndjson = NdjsonWriter::new(writer, self.columns.clone());
+        ndjson.write_rows(self)?;
+        ndjson.finish().map(|_| ())
+    }
+}
+
+/// Writes rows in csv, the header is written before the first row.
+///
+/// Null is an empty field while an empty text is a quoted empty field,
+/// the same as the csv of postgresql.
+pub struct CsvWriter<W: Write> {
+    writer: W,
+    columns: Vec<String>,
+    header_written: bool,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(writer: W, columns: Vec<String>) -> Self {
+        CsvWriter {
+            writer,
+            columns,
+            header_written: false,
+        }
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if !self.header_written {
+            self.header_written = true;
+            let header: Vec<String> = self.columns.iter().map(|c| csv_field(c)).collect();
+            writeln!(self.writer, "{}", header.join(","))?;
+        }
+        Ok(())
+    }
+
+    /// the values are in the order of the columns, the missing ones are written as null
+    pub fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        self.write_header()?;
+        let fields: Vec<String> = (0..self.columns.len())
+            .map(|i| match row.get(i).and_then(to_text) {
+                Some(text) => csv_field(&text),
+                None => String::new(),
+            })
+            .collect();
+        writeln!(self.writer, "{}", fields.join(","))
+    }
+
+    pub fn write_rows(&mut self, rows: &Rows) -> io::Result<()> {
+        for row in rows.data.iter() {
+            self.write_row(row)?;
+        }
+        Ok(())
+    }
+
+    /// write the header if there were no rows, flush and return the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_header()?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Writes rows as newline delimited json, one object per row with the columns as keys
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+    columns: Vec<String>,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(writer: W, columns: Vec<String>) -> Self {
+        NdjsonWriter { writer, columns }
+    }
+
+    /// the keys are written in the order of the columns
+    pub fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        self.writer.write_all(b"{")?;
+        for (i, column) in self.columns.iter().enumerate() {
+            if i > 0 {
+                self.writer.write_all(b",")?;
+            }
+            serde_json::to_writer(&mut self.writer, column)?;
+            self.writer.write_all(b":")?;
+            let value = row.get(i).map(to_json).unwrap_or(serde_json::Value::Null);
+            serde_json::to_writer(&mut self.writer, &value)?;
+        }
+        self.writer.write_all(b"}\n")
+    }
+
+    pub fn write_rows(&mut self, rows: &Rows) -> io::Result<()> {
+        for row in rows.data.iter() {
+            self.write_row(row)?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// quote the field when it has a separator, a quote, a line break or is empty
+fn csv_field(text: &str) -> String {
+    if text.is_empty() || text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// the bytes in the hex format of postgresql: `\x0a0b`
+fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(2 + bytes.len() * 2);
+    hex.push_str("\\x");
+    for b in bytes {
+        hex.push_str(&format!("{:02x}", b));
+    }
+    hex
+}
+
+/// the value as text, None for null.
+/// The json, interval, geometries and arrays are written as their json.
+pub(crate) fn to_text(value: &Value) -> Option<String> {
+    let text = match *value {
+        Value::Nil => return None,
+        Value::Bool(v) => v.to_string(),
+        Value::Tinyint(v) => v.to_string(),
+        Value::Smallint(v) => v.to_string(),
+        Value::Int(v) => v.to_string(),
+        Value::Bigint(v) => v.to_string(),
+        Value::Float(v) => v.to_string(),
+        Value::Double(v) => v.to_string(),
+        Value::BigDecimal(ref v) => v.to_string(),
+        Value::Blob(ref v) => to_hex(v),
+        Value::ImageUri(ref v) => v.clone(),
+        Value::Char(v) => v.to_string(),
+        Value::Text(ref v) => v.clone(),
+        Value::Json(ref v) => v.to_string(),
+        Value::Uuid(ref v) => v.to_string(),
+        Value::Date(ref v) => v.to_string(),
+        Value::Time(ref v) => v.to_string(),
+        Value::TimeTz(ref v) => v.to_string(),
+        Value::Inet(ref v) => v.to_string(),
+        // a cidr is always written with its prefix
+        Value::Cidr(ref v) => format!("{}/{}", v.addr, v.prefix),
+        Value::MacAddr(ref v) => v.to_string(),
+        Value::DateTime(ref v) => v.format(DATETIME_FORMAT).to_string(),
+        Value::Timestamp(ref v) => v.to_rfc3339(),
+        Value::Interval(_)
+        | Value::Point(_)
+        | Value::Line(_)
+        | Value::LineSegment(_)
+        | Value::Box(_)
+        | Value::Path(_)
+        | Value::Polygon(_)
+        | Value::Circle(_)
+        | Value::Geometry(_)
+        | Value::Array(_) => to_json(value).to_string(),
+        Value::Range(ref v) => range_text(v),
+        Value::Multirange(ref v) => {
+            let ranges: Vec<String> = v.iter().map(range_text).collect();
+            format!("{{{}}}", ranges.join(","))
+        }
+        Value::Composite(ref fields) => {
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|(_, v)| to_text(v).map(|text| range_bound(&text)).unwrap_or_default())
+                .collect();
+            format!("({})", fields.join(","))
+        }
+    };
+    Some(text)
+}
+
+/// the range in the text format of postgresql: `[1,5)`, `(,"2020-01-01 00:00:00+00"]` or `empty`
+fn range_text(range: &Range<Value>) -> String {
+    if range.empty {
+        return "empty".to_string();
+    }
+    let bound_text = |bound: &Bound<Value>| match *bound {
+        Bound::Included(ref v) | Bound::Excluded(ref v) => {
+            to_text(v).map(|text| range_bound(&text)).unwrap_or_default()
+        }
+        Bound::Unbounded => String::new(),
+    };
+    let lower = if let Bound::Included(_) = range.lower { '[' } else { '(' };
+    let upper = if let Bound::Included(_) = range.upper { ']' } else { ')' };
+    format!(
+        "{}{},{}{}",
+        lower,
+        bound_text(&range.lower),
+        bound_text(&range.upper),
+        upper
+    )
+}
+
+/// quote the bound of a range or the field of a composite
+/// when it has a separator, a bracket, a quote or a space
+fn range_bound(text: &str) -> String {
+    if text.is_empty() || text.contains(|c: char| "()[],\"\\".contains(c) || c.is_whitespace()) {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// the bound in the serialized form of `Bound`, ie: `{"Included": 1}` or `"Unbounded"`
+fn bound_json(bound: &Bound<Value>) -> serde_json::Value {
+    match *bound {
+        Bound::Included(ref v) => json!({ "Included": to_json(v) }),
+        Bound::Excluded(ref v) => json!({ "Excluded": to_json(v) }),
+        Bound::Unbounded => json!("Unbounded"),
+    }
+}
+
+fn range_json(range: &Range<Value>) -> serde_json::Value {
+    json!({
+        "lower": bound_json(&range.lower),
+        "upper": bound_json(&range.upper),
+        "empty": range.empty,
+    })
+}
+
+/// the value as plain json.
+/// BigDecimal is a string to keep its precision, the non finite floats are null.
+/// A range has the shape of a serialized `Range`, so it can be deserialized into a typed range.
+pub(crate) fn to_json(value: &Value) -> serde_json::Value {
+    match *value {
+        Value::Nil => serde_json::Value::Null,
+        Value::Bool(v) => json!(v),
+        Value::Tinyint(v) => json!(v),
+        Value::Smallint(v) => json!(v),
+        Value::Int(v) => json!(v),
+        Value::Bigint(v) => json!(v),
+        // through the text, so 0.1f32 is 0.1 and not 0.10000000149011612
+        Value::Float(v) => float_json(v.to_string().parse().unwrap_or(f64::NAN)),
+        Value::Double(v) => float_json(v),
+        Value::Json(ref v) => v.clone(),
+        Value::Interval(ref v) => json!({
+            "months": v.months,
+            "days": v.days,
+            "microseconds": v.microseconds,
+        }),
+        Value::Point(ref v) => json!({"x": v.x(), "y": v.y()}),
+        // the geometries in the form of their serialization
+        Value::Line(ref v) => serialized_json(v),
+        Value::LineSegment(ref v) => serialized_json(v),
+        Value::Box(ref v) => serialized_json(v),
+        Value::Path(ref v) => serialized_json(v),
+        Value::Polygon(ref v) => serialized_json(v),
+        Value::Circle(ref v) => serialized_json(v),
+        Value::Geometry(ref v) => serialized_json(v),
+        Value::Array(Array::Int(ref v)) => json!(v),
+        Value::Array(Array::Float(ref v)) => serde_json::Value::Array(
+            v.iter()
+                .map(|f| to_json(&Value::Float(*f)))
+                .collect(),
+        ),
+        Value::Array(Array::Text(ref v)) => json!(v),
+        Value::Range(ref v) => range_json(v),
+        Value::Multirange(ref v) => serde_json::Value::Array(v.iter().map(range_json).collect()),
+        Value::Composite(ref fields) => serde_json::Value::Object(
+            fields.iter().map(|(k, v)| (k.to_owned(), to_json(v))).collect(),
+        ),
+        _ => match to_text(value) {
+            Some(text) => serde_json::Value::String(text),
+            None => serde_json::Value::Null,
+        },
+    }
+}
+
+fn serialized_json<T: serde::Serialize>(v: &T) -> serde_json::Value {
+    serde_json::to_value(v).unwrap_or(serde_json::Value::Null)
+}
+
+fn float_json(v: f64) -> serde_json::Value {
+    serde_json::Number::from_f64(v)
+        .map(serde_json::Value::Number)
+        .unwrap_or(serde_json::Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Interval;
+    use chrono::NaiveDate;
+
+    fn sample() -> Rows {
+        let mut rows = Rows::new(vec![
+            "id".into(),
+            "name".into(),
+            "rate".into(),
+            "tags".into(),
+            "updated".into(),
+        ]);
+        let updated = NaiveDate::from_ymd_opt(2006, 2, 15)
+            .unwrap()
+            .and_hms_opt(4, 34, 33)
+            .unwrap();
+        rows.push(vec![
+            Value::Int(1),
+            Value::Text("line\nbreak".into()),
+            Value::Float(0.99),
+            Value::Array(Array::Text(vec!["a".into(), "b".into()])),
+            Value::DateTime(updated),
+        ]);
+        rows.push(vec![
+            Value::Int(2),
+            Value::Text("".into()),
+            Value::Nil,
+            Value::Nil,
+            Value::Nil,
+        ]);
+        rows
+    }
+
+    #[test]
+    fn csv() {
+        let mut csv = vec![];
+        sample().write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "id,name,rate,tags,updated\n\
+             1,\"line\nbreak\",0.99,\"[\"\"a\"\",\"\"b\"\"]\",2006-02-15T04:34:33\n\
+             2,\"\",,,\n"
+        );
+    }
+
+    #[test]
+    fn csv_header_only() {
+        let mut csv = vec![];
+        Rows::new(vec!["id".into()]).write_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "id\n");
+    }
+
+    #[test]
+    fn ndjson() {
+        let mut ndjson = vec![];
+        sample().write_ndjson(&mut ndjson).unwrap();
+        assert_eq!(
+            String::from_utf8(ndjson).unwrap(),
+            "{\"id\":1,\"name\":\"line\\nbreak\",\"rate\":0.99,\"tags\":[\"a\",\"b\"],\"updated\":\"2006-02-15T04:34:33\"}\n\
+             {\"id\":2,\"name\":\"\",\"rate\":null,\"tags\":null,\"updated\":null}\n"
+        );
+    }
+
+    #[test]
+    fn range_texts() {
+        let range = Range::new(Bound::Included(Value::Int(1)), Bound::Excluded(Value::Int(5)));
+        assert_eq!(to_text(&Value::Range(Box::new(range))).unwrap(), "[1,5)");
+        let date = NaiveDate::from_ymd_opt(2006, 2, 15).unwrap();
+        let time = date.and_hms_opt(4, 34, 33).unwrap();
+        let range = Range::new(Bound::Unbounded, Bound::Included(Value::DateTime(time)));
+        assert_eq!(
+            to_text(&Value::Multirange(vec![range, Range::empty()])).unwrap(),
+            "{(,2006-02-15T04:34:33],empty}"
+        );
+        assert_eq!(range_bound("a \"b\""), "\"a \"\"b\"\"\"");
+        let composite = Value::Composite(vec![
+            ("street".into(), Value::Text("47 MySakila Drive".into())),
+            ("zip".into(), Value::Nil),
+            ("id".into(), Value::Int(1)),
+        ]);
+        assert_eq!(to_text(&composite).unwrap(), "(\"47 MySakila Drive\",,1)");
+        assert_eq!(to_json(&composite), json!({"street": "47 MySakila Drive", "zip": null, "id": 1}));
+    }
+
+    #[test]
+    fn plain_json_values() {
+        assert_eq!(to_json(&Value::Blob(vec![1, 255])), json!("\\x01ff"));
+        assert_eq!(to_json(&Value::Double(f64::NAN)), serde_json::Value::Null);
+        assert_eq!(
+            to_json(&Value::Interval(Interval::new(1, 2, 3))),
+            json!({"months": 3, "days": 2, "microseconds": 1})
+        );
+    }
+}
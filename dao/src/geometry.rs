@@ -0,0 +1,65 @@
+//! The geometric types of postgresql which have no equivalent in geo-types,
+//! and the geometry of postgis with the identifier of its spatial reference system.
+use geo_types::{Geometry, Point};
+use serde::{Deserialize, Serialize};
+
+/// An infinite line of the equation `a*x + b*y + c = 0`, ie: `line`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LineEquation {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+}
+
+impl LineEquation {
+    pub fn new(a: f64, b: f64, c: f64) -> Self {
+        LineEquation { a, b, c }
+    }
+}
+
+/// A circle with its center and radius, ie: `circle`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Circle {
+    pub center: Point<f64>,
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn new(center: Point<f64>, radius: f64) -> Self {
+        Circle { center, radius }
+    }
+}
+
+/// A geometry of postgis, ie: `geometry(Point,4326)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Spatial {
+    pub geometry: Geometry<f64>,
+    /// the spatial reference system, ie: 4326 for WGS 84
+    pub srid: Option<i32>,
+}
+
+impl Spatial {
+    pub fn new(geometry: Geometry<f64>, srid: Option<i32>) -> Self {
+        Spatial { geometry, srid }
+    }
+
+    /// the name of the type of the geometry as postgis calls it,
+    /// a line and a rectangle are written as a linestring and a polygon.
+    pub fn type_name(&self) -> &'static str {
+        match self.geometry {
+            Geometry::Point(_) => "Point",
+            Geometry::Line(_) | Geometry::LineString(_) => "LineString",
+            Geometry::Polygon(_) | Geometry::Rect(_) | Geometry::Triangle(_) => "Polygon",
+            Geometry::MultiPoint(_) => "MultiPoint",
+            Geometry::MultiLineString(_) => "MultiLineString",
+            Geometry::MultiPolygon(_) => "MultiPolygon",
+            Geometry::GeometryCollection(_) => "GeometryCollection",
+        }
+    }
+}
+
+impl From<Geometry<f64>> for Spatial {
+    fn from(geometry: Geometry<f64>) -> Self {
+        Spatial::new(geometry, None)
+    }
+}
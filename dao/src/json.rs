@@ -0,0 +1,193 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::ops::{Deref, DerefMut};
+use crate::error::ConvertError;
+use crate::value::{ToValue, TryToValue};
+use crate::Value;
+
+/// Wrap a field of an entity with this, to store it in a json or jsonb column
+/// ```rust
+/// use wumn_dao::{Json, Value};
+/// use std::convert::TryFrom;
+///
+/// let tags = Json(vec!["drama".to_string(), "comedy".to_string()]);
+/// let value: Value = (&tags).into();
+/// let back: Json<Vec<String>> = TryFrom::try_from(&value).unwrap();
+/// assert_eq!(tags, back);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+/// A value which can not be represented in json, such as a map with keys that are not strings,
+/// is null here, as `From` can not fail. The derived `ToDao::try_to_dao` used when
+/// the entity is saved converts the field with `TryToValue` instead, returning the error.
+impl<'a, T> From<&'a Json<T>> for Value
+where
+    T: Serialize,
+{
+    fn from(json: &'a Json<T>) -> Self {
+        match serde_json::to_value(&json.0) {
+            Ok(v) => Value::Json(v),
+            Err(_) => Value::Nil,
+        }
+    }
+}
+
+impl<T> From<Json<T>> for Value
+where
+    T: Serialize,
+{
+    fn from(json: Json<T>) -> Self {
+        From::from(&json)
+    }
+}
+
+impl<'a, T> From<&'a Option<Json<T>>> for Value
+where
+    T: Serialize,
+{
+    fn from(json: &'a Option<Json<T>>) -> Self {
+        match *json {
+            Some(ref json) => From::from(json),
+            None => Value::Nil,
+        }
+    }
+}
+
+impl<T> ToValue for Json<T>
+where
+    T: Serialize,
+{
+    fn to_value(&self) -> Value {
+        self.into()
+    }
+}
+
+impl<T> TryToValue for Json<T>
+where
+    T: Serialize,
+{
+    fn try_to_value(&self) -> Result<Value, ConvertError> {
+        serde_json::to_value(&self.0)
+            .map(Value::Json)
+            .map_err(ConvertError::JsonError)
+    }
+}
+
+impl<T> TryToValue for Option<Json<T>>
+where
+    T: Serialize,
+{
+    fn try_to_value(&self) -> Result<Value, ConvertError> {
+        match *self {
+            Some(ref json) => json.try_to_value(),
+            None => Ok(Value::Nil),
+        }
+    }
+}
+
+/// the json can also be read from a text column
+impl<'a, T> TryFrom<&'a Value> for Json<T>
+where
+    T: DeserializeOwned,
+{
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Json(ref v) => serde_json::from_value(v.clone())
+                .map(Json)
+                .map_err(ConvertError::JsonError),
+            Value::Text(ref v) => serde_json::from_str(v)
+                .map(Json)
+                .map_err(ConvertError::JsonError),
+            _ => Err(ConvertError::NotSupported(
+                format!("{:?}", value),
+                "Json".to_string(),
+            )),
+        }
+    }
+}
+
+impl<'a, T> TryFrom<&'a Value> for Option<Json<T>>
+where
+    T: DeserializeOwned,
+{
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Nil => Ok(None),
+            _ => TryFrom::try_from(value).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Address {
+        city: String,
+        zip: Option<String>,
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let address = Json(Address {
+            city: "Manila".into(),
+            zip: None,
+        });
+        let value: Value = (&address).into();
+        assert_eq!(
+            value,
+            Value::Json(serde_json::json!({"city": "Manila", "zip": null}))
+        );
+        let back: Json<Address> = TryFrom::try_from(&value).unwrap();
+        assert_eq!(address, back);
+    }
+
+    #[test]
+    fn json_from_text_and_nil() {
+        let value = Value::Text(r#"{"a": 1}"#.into());
+        let map: Json<BTreeMap<String, i32>> = TryFrom::try_from(&value).unwrap();
+        assert_eq!(map.get("a"), Some(&1));
+        let none: Option<Json<Address>> = TryFrom::try_from(&Value::Nil).unwrap();
+        assert_eq!(none, None);
+        let invalid: Result<Json<Address>, _> = TryFrom::try_from(&Value::Int(1));
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn unserializable_json_is_nil() {
+        let mut map = BTreeMap::new();
+        map.insert(vec![1], 1);
+        assert_eq!(Value::from(&Json(map.clone())), Value::Nil);
+        assert!(Json(map.clone()).try_to_value().is_err());
+        assert!(Some(Json(map)).try_to_value().is_err());
+        assert_eq!(None::<Json<Address>>.try_to_value().unwrap(), Value::Nil);
+    }
+}
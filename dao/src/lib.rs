@@ -3,17 +3,41 @@
 
 pub use column_name::{ColumnName, ToColumnNames};
 pub use dao::{Dao, FromDao, ToDao};
+pub use db_enum::DbEnum;
+pub use de::{from_dao, from_value};
 pub use error::{ConvertError, DaoError};
+pub use export::{CsvWriter, NdjsonWriter};
+pub use geometry::{Circle, LineEquation, Spatial};
 pub use table_name::{TableName, ToTableName};
-pub use value::{ToValue, Value, Array};
+pub use value::{data_uri_bytes, DbValue, ToValue, TryToValue, Value, Array};
 pub use interval::Interval;
-pub use rows::Rows;
+pub use json::Json;
+pub use network::{IpNetwork, MacAddr};
+pub use range::Range;
+#[cfg(feature = "with-arrow")]
+pub use record_batch::{arrow_type, list_type};
+pub use rows::{Row, RowIter, Rows};
+pub use ser::{to_dao, to_value};
+pub use time_tz::TimeTz;
 
 mod column_name;
 mod common;
 mod dao;
+mod db_enum;
+mod de;
 mod error;
+mod export;
+mod geometry;
 mod interval;
+mod json;
+mod network;
+mod range;
+#[cfg(feature = "with-arrow")]
+mod record_batch;
 mod rows;
+mod ser;
 mod table_name;
+mod time_tz;
+#[cfg(feature = "with-time")]
+mod time_types;
 pub mod value;
@@ -0,0 +1,138 @@
+//! The network address types: `inet` and `cidr` are an ip address with the length
+//! of its network prefix, `macaddr` is the 6 bytes of a mac address.
+use crate::error::ConvertError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// An ip address with the number of bits of its network prefix,
+/// ie: `192.168.100.128/25`. A single host has the full prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct IpNetwork {
+    pub addr: IpAddr,
+    pub prefix: u8,
+}
+
+impl IpNetwork {
+    /// None when the prefix is longer than the address
+    pub fn new(addr: IpAddr, prefix: u8) -> Option<Self> {
+        if prefix <= max_prefix(&addr) {
+            Some(IpNetwork { addr, prefix })
+        } else {
+            None
+        }
+    }
+
+    /// the address of a single host, with the full prefix
+    pub fn host(addr: IpAddr) -> Self {
+        IpNetwork {
+            prefix: max_prefix(&addr),
+            addr,
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.prefix == max_prefix(&self.addr)
+    }
+}
+
+fn max_prefix(addr: &IpAddr) -> u8 {
+    match *addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+impl From<IpAddr> for IpNetwork {
+    fn from(addr: IpAddr) -> Self {
+        IpNetwork::host(addr)
+    }
+}
+
+/// formatted the way postgresql does for inet, the prefix is omitted for a single host
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_host() {
+            write!(f, "{}", self.addr)
+        } else {
+            write!(f, "{}/{}", self.addr, self.prefix)
+        }
+    }
+}
+
+/// parse `10.1.0.0/16` or a single host `10.1.0.1`
+impl FromStr for IpNetwork {
+    type Err = ConvertError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let not_supported = || ConvertError::NotSupported(s.to_string(), "IpNetwork".into());
+        let mut parts = s.trim().splitn(2, '/');
+        let addr: IpAddr = parts
+            .next()
+            .and_then(|addr| addr.parse().ok())
+            .ok_or_else(not_supported)?;
+        match parts.next() {
+            Some(prefix) => {
+                let prefix = prefix.parse().map_err(|_| not_supported())?;
+                IpNetwork::new(addr, prefix).ok_or_else(not_supported)
+            }
+            None => Ok(IpNetwork::host(addr)),
+        }
+    }
+}
+
+/// A mac address, ie: `08:00:2b:01:02:03`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let b = self.0;
+        write!(
+            f,
+            "{:02x}:{:02x}:{:02x}:{:02x}:{:02x}:{:02x}",
+            b[0], b[1], b[2], b[3], b[4], b[5]
+        )
+    }
+}
+
+/// parse the 6 bytes in hex separated by `:` or `-`
+impl FromStr for MacAddr {
+    type Err = ConvertError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let not_supported = || ConvertError::NotSupported(s.to_string(), "MacAddr".into());
+        let parts: Vec<&str> = s.trim().split([':', '-']).collect();
+        if parts.len() != 6 {
+            return Err(not_supported());
+        }
+        let mut bytes = [0; 6];
+        for (byte, part) in bytes.iter_mut().zip(parts) {
+            if part.len() != 2 {
+                return Err(not_supported());
+            }
+            *byte = u8::from_str_radix(part, 16).map_err(|_| not_supported())?;
+        }
+        Ok(MacAddr(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_format() {
+        let network: IpNetwork = "192.168.100.128/25".parse().unwrap();
+        assert_eq!(network.prefix, 25);
+        assert_eq!(network.to_string(), "192.168.100.128/25");
+        let host: IpNetwork = "2001:db8::1".parse().unwrap();
+        assert_eq!(host.prefix, 128);
+        assert_eq!(host.to_string(), "2001:db8::1");
+        assert!("10.0.0.1/33".parse::<IpNetwork>().is_err());
+        let mac: MacAddr = "08-00-2B-01-02-03".parse().unwrap();
+        assert_eq!(mac.to_string(), "08:00:2b:01:02:03");
+        assert!("08:00:2b:01:02".parse::<MacAddr>().is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! A range of values with its lower and upper bound, ie: `tstzrange` or `int4range`.
+use crate::error::ConvertError;
+use crate::value::ToValue;
+use crate::Value;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::ops::{self, Bound};
+
+/// The bounds are inclusive, exclusive or unbounded, ie: `[2020-01-01,2020-02-01)`.
+/// An empty range has no bounds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Range<T> {
+    pub lower: Bound<T>,
+    pub upper: Bound<T>,
+    pub empty: bool,
+}
+
+impl<T> Range<T> {
+    pub fn new(lower: Bound<T>, upper: Bound<T>) -> Self {
+        Range {
+            lower,
+            upper,
+            empty: false,
+        }
+    }
+
+    pub fn empty() -> Self {
+        Range {
+            lower: Bound::Unbounded,
+            upper: Bound::Unbounded,
+            empty: true,
+        }
+    }
+
+    pub fn map<U, F>(self, mut f: F) -> Range<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        Range {
+            lower: map_bound(self.lower, &mut f),
+            upper: map_bound(self.upper, &mut f),
+            empty: self.empty,
+        }
+    }
+}
+
+fn map_bound<T, U, F>(bound: Bound<T>, f: &mut F) -> Bound<U>
+where
+    F: FnMut(T) -> U,
+{
+    match bound {
+        Bound::Included(v) => Bound::Included(f(v)),
+        Bound::Excluded(v) => Bound::Excluded(f(v)),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+fn try_bound<'a, T>(bound: &'a Bound<Value>) -> Result<Bound<T>, ConvertError>
+where
+    T: TryFrom<&'a Value, Error = ConvertError>,
+{
+    match *bound {
+        Bound::Included(ref v) => T::try_from(v).map(Bound::Included),
+        Bound::Excluded(ref v) => T::try_from(v).map(Bound::Excluded),
+        Bound::Unbounded => Ok(Bound::Unbounded),
+    }
+}
+
+/// `start..end` includes the start and excludes the end
+impl<T> From<ops::Range<T>> for Range<T> {
+    fn from(range: ops::Range<T>) -> Self {
+        Range::new(Bound::Included(range.start), Bound::Excluded(range.end))
+    }
+}
+
+impl<T> From<ops::RangeInclusive<T>> for Range<T> {
+    fn from(range: ops::RangeInclusive<T>) -> Self {
+        let (start, end) = range.into_inner();
+        Range::new(Bound::Included(start), Bound::Included(end))
+    }
+}
+
+impl<T> From<ops::RangeFrom<T>> for Range<T> {
+    fn from(range: ops::RangeFrom<T>) -> Self {
+        Range::new(Bound::Included(range.start), Bound::Unbounded)
+    }
+}
+
+impl<T: Into<Value>> From<Range<T>> for Value {
+    fn from(f: Range<T>) -> Self {
+        Value::Range(Box::new(f.map(Into::into)))
+    }
+}
+
+impl<'a, T: Clone + Into<Value>> From<&'a Range<T>> for Value {
+    fn from(f: &'a Range<T>) -> Self {
+        From::from(f.to_owned())
+    }
+}
+
+impl<'a, T: Clone + Into<Value>> From<&'a Option<Range<T>>> for Value {
+    fn from(f: &'a Option<Range<T>>) -> Self {
+        match *f {
+            Some(ref f) => From::from(f),
+            None => Value::Nil,
+        }
+    }
+}
+
+impl<T: Clone + Into<Value>> ToValue for Range<T> {
+    fn to_value(&self) -> Value {
+        self.into()
+    }
+}
+
+impl<'a, T> TryFrom<&'a Value> for Range<T>
+where
+    T: TryFrom<&'a Value, Error = ConvertError>,
+{
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Range(ref v) => Ok(Range {
+                lower: try_bound(&v.lower)?,
+                upper: try_bound(&v.upper)?,
+                empty: v.empty,
+            }),
+            _ => Err(ConvertError::NotSupported(
+                format!("{:?}", value),
+                "Range".into(),
+            )),
+        }
+    }
+}
+
+impl<'a, T> TryFrom<&'a Value> for Option<Range<T>>
+where
+    T: TryFrom<&'a Value, Error = ConvertError>,
+{
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Nil => Ok(None),
+            _ => TryFrom::try_from(value).map(Some),
+        }
+    }
+}
+
+/// a multirange is a list of ranges
+impl<T: Into<Value>> From<Vec<Range<T>>> for Value {
+    fn from(f: Vec<Range<T>>) -> Self {
+        Value::Multirange(f.into_iter().map(|r| r.map(Into::into)).collect())
+    }
+}
+
+impl<'a, T: Clone + Into<Value>> From<&'a Vec<Range<T>>> for Value {
+    fn from(f: &'a Vec<Range<T>>) -> Self {
+        From::from(f.to_owned())
+    }
+}
+
+impl<'a, T> TryFrom<&'a Value> for Vec<Range<T>>
+where
+    T: TryFrom<&'a Value, Error = ConvertError>,
+{
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Multirange(ref v) => v
+                .iter()
+                .map(|r| {
+                    Ok(Range {
+                        lower: try_bound(&r.lower)?,
+                        upper: try_bound(&r.upper)?,
+                        empty: r.empty,
+                    })
+                })
+                .collect(),
+            _ => Err(ConvertError::NotSupported(
+                format!("{:?}", value),
+                "Vec<Range>".into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    #[test]
+    fn typed_ranges() {
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 14, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2020, 1, 3, 11, 0, 0).unwrap();
+        let period: Range<DateTime<Utc>> = (start..end).into();
+        let value: Value = (&period).into();
+        assert_eq!(
+            value,
+            Value::Range(Box::new(Range::new(
+                Bound::Included(Value::Timestamp(start)),
+                Bound::Excluded(Value::Timestamp(end))
+            )))
+        );
+        assert_eq!(Range::<DateTime<Utc>>::try_from(&value).unwrap(), period);
+        let from: Option<Range<i32>> = TryFrom::try_from(&Value::from(Range::from(5..))).unwrap();
+        assert_eq!(from, Some(Range::new(Bound::Included(5), Bound::Unbounded)));
+        let empty = Value::from(Range::<i32>::empty());
+        assert!(Range::<i32>::try_from(&empty).unwrap().empty);
+        assert!(Range::<i32>::try_from(&Value::Int(1)).is_err());
+        let deserialized: Range<DateTime<Utc>> = crate::from_value(&value).unwrap();
+        assert_eq!(deserialized, period);
+        let ranges: Vec<Range<i64>> = vec![(1..3).into(), (5..=7).into()];
+        assert_eq!(Vec::<Range<i64>>::try_from(&Value::from(&ranges)).unwrap(), ranges);
+    }
+}
@@ -0,0 +1,359 @@
+//! Convert rows into an arrow `RecordBatch`, with the column types inferred from the values
+//! or given by the caller, ie: from the `SqlType` of the columns the rows are selected from.
+use arrow_array::{
+    builder::{
+        BinaryBuilder, BooleanBuilder, Date32Builder, Float32Builder, Float64Builder,
+        Int16Builder, Int32Builder, Int64Builder, Int8Builder, IntervalMonthDayNanoBuilder,
+        ListBuilder, StringBuilder, Time64MicrosecondBuilder, TimestampMicrosecondBuilder,
+    },
+    types::IntervalMonthDayNanoType,
+    ArrayRef, NullArray, RecordBatch, RecordBatchOptions,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema, TimeUnit};
+use chrono::{NaiveDate, Timelike};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use crate::export::to_text;
+use crate::value::Array;
+use crate::{Rows, Value};
+
+impl Rows {
+    /// convert the rows into a record batch,
+    /// the type of each column is the arrow type of its first non null value
+    /// ```rust
+    /// use wumn_dao::{Rows, Value};
+    /// use arrow_schema::DataType;
+    ///
+    /// let mut rows = Rows::new(vec!["actor_id".into(), "first_name".into()]);
+    /// rows.push(vec![Value::Int(1), Value::Text("PENELOPE".into())]);
+    /// let batch = rows.to_record_batch().unwrap();
+    /// assert_eq!(batch.num_rows(), 1);
+    /// assert_eq!(batch.schema().field(0).data_type(), &DataType::Int32);
+    /// ```
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ArrowError> {
+        let data_types: Vec<DataType> = (0..self.columns.len())
+            .map(|i| {
+                self.data
+                    .iter()
+                    .filter_map(|row| row.get(i))
+                    .find(|v| !v.is_nil())
+                    .map(arrow_type)
+                    .unwrap_or(DataType::Null)
+            })
+            .collect();
+        self.to_record_batch_with_types(&data_types)
+    }
+
+    /// convert the rows into a record batch with these column types.
+    /// The integers are widened and any value can be written in a Utf8 column,
+    /// the other values have to match the type of their column.
+    pub fn to_record_batch_with_types(
+        &self,
+        data_types: &[DataType],
+    ) -> Result<RecordBatch, ArrowError> {
+        if data_types.len() != self.columns.len() {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "{} data types given for {} columns",
+                data_types.len(),
+                self.columns.len()
+            )));
+        }
+        let fields: Vec<Field> = self
+            .columns
+            .iter()
+            .zip(data_types.iter())
+            .map(|(column, data_type)| Field::new(column.as_str(), data_type.clone(), true))
+            .collect();
+        let arrays = data_types
+            .iter()
+            .enumerate()
+            .map(|(i, data_type)| {
+                let values = self.data.iter().map(|row| row.get(i).unwrap_or(&Value::Nil));
+                build_array(values, self.data.len(), data_type)
+            })
+            .collect::<Result<Vec<ArrayRef>, ArrowError>>()?;
+        let options = RecordBatchOptions::new().with_row_count(Some(self.data.len()));
+        RecordBatch::try_new_with_options(Arc::new(Schema::new(fields)), arrays, &options)
+    }
+}
+
+/// the arrow type which holds this value without loss.
+/// BigDecimal, json, uuid, time with time zone, network addresses, geometries
+/// and ranges are stored as text.
+pub fn arrow_type(value: &Value) -> DataType {
+    match *value {
+        Value::Nil => DataType::Null,
+        Value::Bool(_) => DataType::Boolean,
+        Value::Tinyint(_) => DataType::Int8,
+        Value::Smallint(_) => DataType::Int16,
+        Value::Int(_) => DataType::Int32,
+        Value::Bigint(_) => DataType::Int64,
+        Value::Float(_) => DataType::Float32,
+        Value::Double(_) => DataType::Float64,
+        Value::Blob(_) => DataType::Binary,
+        Value::Date(_) => DataType::Date32,
+        Value::Time(_) => DataType::Time64(TimeUnit::Microsecond),
+        Value::DateTime(_) => DataType::Timestamp(TimeUnit::Microsecond, None),
+        Value::Timestamp(_) => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        Value::Interval(_) => DataType::Interval(arrow_schema::IntervalUnit::MonthDayNano),
+        Value::Array(Array::Int(_)) => list_type(DataType::Int32),
+        Value::Array(Array::Float(_)) => list_type(DataType::Float32),
+        Value::Array(Array::Text(_)) => list_type(DataType::Utf8),
+        Value::BigDecimal(_)
+        | Value::ImageUri(_)
+        | Value::Char(_)
+        | Value::Text(_)
+        | Value::Json(_)
+        | Value::Uuid(_)
+        | Value::TimeTz(_)
+        | Value::Inet(_)
+        | Value::Cidr(_)
+        | Value::MacAddr(_)
+        | Value::Point(_)
+        | Value::Line(_)
+        | Value::LineSegment(_)
+        | Value::Box(_)
+        | Value::Path(_)
+        | Value::Polygon(_)
+        | Value::Circle(_)
+        | Value::Geometry(_)
+        | Value::Range(_)
+        | Value::Multirange(_)
+        | Value::Composite(_) => DataType::Utf8,
+    }
+}
+
+/// a list of nullable items of this type
+pub fn list_type(data_type: DataType) -> DataType {
+    DataType::List(Arc::new(Field::new("item", data_type, true)))
+}
+
+fn mismatch(value: &Value, data_type: &DataType) -> ArrowError {
+    ArrowError::CastError(format!(
+        "unable to write {:?} in a column of {}",
+        value, data_type
+    ))
+}
+
+/// append each of the values to the builder,
+/// the conversion returns None when the value does not fit in the column
+macro_rules! build {
+    ($values:expr, $data_type:expr, $builder:expr, $convert:expr) => {{
+        let mut builder = $builder;
+        for value in $values {
+            match *value {
+                Value::Nil => builder.append_null(),
+                _ => match $convert(value) {
+                    Some(v) => builder.append_value(v),
+                    None => return Err(mismatch(value, $data_type)),
+                },
+            }
+        }
+        Ok(Arc::new(builder.finish()) as ArrayRef)
+    }};
+}
+
+fn build_array<'a, I>(values: I, len: usize, data_type: &DataType) -> Result<ArrayRef, ArrowError>
+where
+    I: Iterator<Item = &'a Value>,
+{
+    match *data_type {
+        DataType::Null => {
+            let mut values = values;
+            match values.find(|v| !v.is_nil()) {
+                Some(value) => Err(mismatch(value, data_type)),
+                None => Ok(Arc::new(NullArray::new(len)) as ArrayRef),
+            }
+        }
+        DataType::Boolean => build!(values, data_type, BooleanBuilder::new(), |v: &Value| {
+            match *v {
+                Value::Bool(v) => Some(v),
+                _ => None,
+            }
+        }),
+        DataType::Int8 => build!(values, data_type, Int8Builder::new(), |v: &Value| {
+            match *v {
+                Value::Tinyint(v) => Some(v),
+                _ => None,
+            }
+        }),
+        DataType::Int16 => build!(values, data_type, Int16Builder::new(), |v: &Value| {
+            match *v {
+                Value::Tinyint(v) => Some(i16::from(v)),
+                Value::Smallint(v) => Some(v),
+                _ => None,
+            }
+        }),
+        DataType::Int32 => build!(values, data_type, Int32Builder::new(), |v: &Value| {
+            match *v {
+                Value::Tinyint(v) => Some(i32::from(v)),
+                Value::Smallint(v) => Some(i32::from(v)),
+                Value::Int(v) => Some(v),
+                _ => None,
+            }
+        }),
+        DataType::Int64 => build!(values, data_type, Int64Builder::new(), |v: &Value| {
+            match *v {
+                Value::Tinyint(v) => Some(i64::from(v)),
+                Value::Smallint(v) => Some(i64::from(v)),
+                Value::Int(v) => Some(i64::from(v)),
+                Value::Bigint(v) => Some(v),
+                _ => None,
+            }
+        }),
+        DataType::Float32 => build!(values, data_type, Float32Builder::new(), |v: &Value| {
+            match *v {
+                Value::Float(v) => Some(v),
+                _ => None,
+            }
+        }),
+        DataType::Float64 => build!(values, data_type, Float64Builder::new(), |v: &Value| {
+            match *v {
+                Value::Float(v) => Some(f64::from(v)),
+                Value::Double(v) => Some(v),
+                Value::BigDecimal(ref v) => v.to_string().parse().ok(),
+                _ => None,
+            }
+        }),
+        DataType::Utf8 => build!(values, data_type, StringBuilder::new(), to_text),
+        DataType::Binary => build!(values, data_type, BinaryBuilder::new(), |v: &'a Value| {
+            match *v {
+                Value::Blob(ref v) => Some(v.as_slice()),
+                _ => None,
+            }
+        }),
+        DataType::Date32 => build!(values, data_type, Date32Builder::new(), |v: &Value| {
+            match *v {
+                Value::Date(ref v) => days_from_epoch(v),
+                _ => None,
+            }
+        }),
+        DataType::Time64(TimeUnit::Microsecond) => build!(
+            values,
+            data_type,
+            Time64MicrosecondBuilder::new(),
+            |v: &Value| match *v {
+                Value::Time(ref v) => Some(
+                    i64::from(v.num_seconds_from_midnight()) * 1_000_000
+                        + i64::from(v.nanosecond()) / 1_000
+                ),
+                _ => None,
+            }
+        ),
+        DataType::Timestamp(TimeUnit::Microsecond, ref tz) => build!(
+            values,
+            data_type,
+            TimestampMicrosecondBuilder::new().with_timezone_opt(tz.clone()),
+            |v: &Value| match *v {
+                Value::DateTime(ref v) => Some(v.and_utc().timestamp_micros()),
+                Value::Timestamp(ref v) => Some(v.timestamp_micros()),
+                _ => None,
+            }
+        ),
+        DataType::Interval(arrow_schema::IntervalUnit::MonthDayNano) => build!(
+            values,
+            data_type,
+            IntervalMonthDayNanoBuilder::new(),
+            |v: &Value| match *v {
+                Value::Interval(ref v) => v.microseconds.checked_mul(1_000).map(|nanos| {
+                    IntervalMonthDayNanoType::make_value(v.months, v.days, nanos)
+                }),
+                _ => None,
+            }
+        ),
+        DataType::List(ref field) => match *field.data_type() {
+            DataType::Int32 => build!(
+                values,
+                data_type,
+                ListBuilder::new(Int32Builder::new()).with_field(field.clone()),
+                |v: &'a Value| match *v {
+                    Value::Array(Array::Int(ref v)) => Some(v.iter().map(|i| Some(*i))),
+                    _ => None,
+                }
+            ),
+            DataType::Float32 => build!(
+                values,
+                data_type,
+                ListBuilder::new(Float32Builder::new()).with_field(field.clone()),
+                |v: &'a Value| match *v {
+                    Value::Array(Array::Float(ref v)) => Some(v.iter().map(|f| Some(*f))),
+                    _ => None,
+                }
+            ),
+            DataType::Utf8 => build!(
+                values,
+                data_type,
+                ListBuilder::new(StringBuilder::new()).with_field(field.clone()),
+                |v: &'a Value| match *v {
+                    Value::Array(Array::Text(ref v)) => Some(v.iter().map(Some)),
+                    _ => None,
+                }
+            ),
+            _ => Err(ArrowError::NotYetImplemented(format!(
+                "writing rows in a column of {}",
+                data_type
+            ))),
+        },
+        _ => Err(ArrowError::NotYetImplemented(format!(
+            "writing rows in a column of {}",
+            data_type
+        ))),
+    }
+}
+
+fn days_from_epoch(date: &NaiveDate) -> Option<i32> {
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1)?;
+    i32::try_from(date.signed_duration_since(epoch).num_days()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Array as _, Int64Array, ListArray, StringArray, TimestampMicrosecondArray};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn inferred_types() {
+        let mut rows = Rows::new(vec!["id".into(), "tags".into(), "updated".into(), "none".into()]);
+        let updated = Utc.with_ymd_and_hms(2006, 2, 15, 4, 34, 33).unwrap();
+        rows.push(vec![
+            Value::Nil,
+            Value::Array(Array::Text(vec!["a".into()])),
+            Value::Timestamp(updated),
+            Value::Nil,
+        ]);
+        rows.push(vec![Value::Bigint(2), Value::Nil, Value::Nil, Value::Nil]);
+        let batch = rows.to_record_batch().unwrap();
+        let schema = batch.schema();
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64);
+        assert_eq!(schema.field(1).data_type(), &list_type(DataType::Utf8));
+        assert_eq!(schema.field(3).data_type(), &DataType::Null);
+        let ids = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+        assert!(ids.is_null(0));
+        assert_eq!(ids.value(1), 2);
+        let tags = batch.column(1).as_any().downcast_ref::<ListArray>().unwrap();
+        assert_eq!(tags.value_length(0), 1);
+        assert!(tags.is_null(1));
+        let updated_column = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap();
+        assert_eq!(updated_column.value(0), updated.timestamp_micros());
+    }
+
+    #[test]
+    fn given_types() {
+        let mut rows = Rows::new(vec!["id".into(), "rate".into()]);
+        rows.push(vec![Value::Smallint(1), Value::Double(0.99)]);
+        let batch = rows
+            .to_record_batch_with_types(&[DataType::Int64, DataType::Utf8])
+            .unwrap();
+        let rates = batch.column(1).as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(rates.value(0), "0.99");
+        assert!(rows
+            .to_record_batch_with_types(&[DataType::Boolean, DataType::Utf8])
+            .is_err());
+        assert!(rows.to_record_batch_with_types(&[DataType::Int64]).is_err());
+    }
+}
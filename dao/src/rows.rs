@@ -1,6 +1,11 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::Debug;
 use std::slice;
+use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
-use crate::dao::Dao;
+use crate::dao::{Dao, FromDao};
+use crate::error::DaoError;
 use crate::value::Value;
 
 /// use this to store data retrieved from the database
@@ -29,41 +34,383 @@ impl Rows {
         self.data.push(row)
     }
 
+    /// the number of rows
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// the position of this column in the rows
+    pub fn column_index(&self, column: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == column)
+    }
+
+    /// the value at this row and column index
+    pub fn get_value(&self, row: usize, column: usize) -> Option<&Value> {
+        self.data.get(row).and_then(|r| r.get(column))
+    }
+
+    /// the value at this row in this column
+    pub fn get_value_by_name(&self, row: usize, column: &str) -> Option<&Value> {
+        self.column_index(column).and_then(|i| self.get_value(row, i))
+    }
+
+    /// the value at this row in this column, converted to T
+    pub fn get<'a, T>(&'a self, row: usize, column: &str) -> Result<T, DaoError<'a, T>>
+    where
+        T: TryFrom<&'a Value>,
+        T::Error: Debug,
+    {
+        match self.row(row) {
+            Some(row) => row.get(column),
+            None => Err(DaoError::NoSuchValueError(column.into())),
+        }
+    }
+
+    /// all the values of this column converted to T
+    /// ```rust
+    /// use wumn_dao::{Rows, Value};
+    ///
+    /// let mut rows = Rows::new(vec!["id".into()]);
+    /// rows.push(vec![Value::Bigint(1)]);
+    /// rows.push(vec![Value::Bigint(2)]);
+    /// let ids = rows.column::<i64>("id").unwrap();
+    /// assert_eq!(ids, vec![1, 2]);
+    /// ```
+    pub fn column<'a, T>(&'a self, column: &str) -> Result<Vec<T>, DaoError<'a, T>>
+    where
+        T: TryFrom<&'a Value>,
+        T::Error: Debug,
+    {
+        let index = self
+            .column_index(column)
+            .ok_or_else(|| DaoError::NoSuchValueError(column.into()))?;
+        self.data
+            .iter()
+            .map(|row| match row.get(index) {
+                Some(v) => TryFrom::try_from(v).map_err(DaoError::ConvertError),
+                None => Err(DaoError::NoSuchValueError(column.into())),
+            })
+            .collect()
+    }
+
+    /// a view of the row at this index
+    pub fn row(&self, index: usize) -> Option<Row<'_>> {
+        self.data.get(index).map(|values| Row {
+            columns: &self.columns,
+            values,
+        })
+    }
+
+    /// Returns an iterator over the `Row`s, borrowing their values
+    pub fn rows(&self) -> RowIter<'_> {
+        RowIter {
+            columns: &self.columns,
+            iter: self.data.iter(),
+        }
+    }
+
     /// Returns an iterator over the `Row`s.
-    pub fn iter(&self) -> Iter {
+    pub fn iter(&self) -> Iter<'_> {
         Iter {
-            columns: self.columns.clone(),
+            columns: &self.columns,
             iter: self.data.iter(),
         }
     }
+
+    /// convert each of the rows into an entity
+    pub fn into_entities<T>(self) -> Vec<T>
+    where
+        T: FromDao,
+    {
+        self.iter().map(|dao| T::from_dao(&dao)).collect()
+    }
+
+    /// sort the rows by the values of this column, null values are last,
+    /// a column that is not in the rows leaves them as is
+    pub fn sort_by_column(&mut self, column: &str) {
+        if let Some(index) = self.column_index(column) {
+            self.data.sort_by(|a, b| compare(a.get(index), b.get(index)));
+        }
+    }
+
+    /// sort the rows by the values of this column in descending order, null values are first
+    pub fn sort_by_column_desc(&mut self, column: &str) {
+        if let Some(index) = self.column_index(column) {
+            self.data.sort_by(|a, b| compare(b.get(index), a.get(index)));
+        }
+    }
+
+    /// sort the rows with a comparator of their views
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&Row, &Row) -> Ordering,
+    {
+        let columns = &self.columns;
+        self.data
+            .sort_by(|a, b| compare(&Row { columns, values: a }, &Row { columns, values: b }));
+    }
+
+    /// keep only the rows where the predicate is true
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&Row) -> bool,
+    {
+        let columns = &self.columns;
+        self.data.retain(|values| predicate(&Row { columns, values }));
+    }
+
+    /// the rows where the value of this column matches the predicate,
+    /// none of them when the column is not in the rows
+    pub fn filter_by_column<F>(&self, column: &str, mut predicate: F) -> Rows
+    where
+        F: FnMut(&Value) -> bool,
+    {
+        let mut rows = Rows::new(self.columns.clone());
+        if let Some(index) = self.column_index(column) {
+            rows.data = self
+                .data
+                .iter()
+                .filter(|row| row.get(index).map(&mut predicate).unwrap_or(false))
+                .cloned()
+                .collect();
+        }
+        rows
+    }
+}
+
+/// Order the values the way the database would,
+/// the numbers are compared across their types and null is greater than everything.
+/// Values of different types are ordered by a fixed rank of their type
+/// and values of types that are not comparable are equal, so the order is total.
+fn compare(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    let a = a.unwrap_or(&Value::Nil);
+    let b = b.unwrap_or(&Value::Nil);
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Char(a), Value::Char(b)) => a.cmp(b),
+        (Value::Text(a), Value::Text(b)) => a.cmp(b),
+        (Value::Blob(a), Value::Blob(b)) => a.cmp(b),
+        (Value::Uuid(a), Value::Uuid(b)) => a.cmp(b),
+        (Value::Date(a), Value::Date(b)) => a.cmp(b),
+        (Value::Time(a), Value::Time(b)) => a.cmp(b),
+        (Value::DateTime(a), Value::DateTime(b)) => a.cmp(b),
+        (Value::Timestamp(a), Value::Timestamp(b)) => a.cmp(b),
+        _ if rank(a) == NUMBER_RANK && rank(b) == NUMBER_RANK => compare_numbers(a, b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// the rank shared by all the number types
+const NUMBER_RANK: u8 = 1;
+
+/// the order of the values of different types, null is last
+fn rank(value: &Value) -> u8 {
+    match *value {
+        Value::Bool(_) => 0,
+        Value::Tinyint(_)
+        | Value::Smallint(_)
+        | Value::Int(_)
+        | Value::Bigint(_)
+        | Value::Float(_)
+        | Value::Double(_)
+        | Value::BigDecimal(_) => NUMBER_RANK,
+        Value::Char(_) => 2,
+        Value::Text(_) => 3,
+        Value::Blob(_) => 4,
+        Value::Uuid(_) => 5,
+        Value::Date(_) => 6,
+        Value::Time(_) => 7,
+        Value::DateTime(_) => 8,
+        Value::Timestamp(_) => 9,
+        Value::Nil => u8::MAX,
+        _ => 10,
+    }
+}
+
+/// the numbers are ordered by their exact value with NaN greater than infinity,
+/// the ties of equal values such as `1` and `1.0` are broken by their type
+/// and then -0.0 is before 0.0
+fn compare_numbers(a: &Value, b: &Value) -> Ordering {
+    match (number(a), number(b)) {
+        (Some(an), Some(bn)) => compare_exact(&an, &bn)
+            .then_with(|| number_rank(a).cmp(&number_rank(b)))
+            .then_with(|| match (an, bn) {
+                (Number::Float(a), Number::Float(b)) => a.total_cmp(&b),
+                _ => Ordering::Equal,
+            }),
+        _ => Ordering::Equal,
+    }
+}
+
+/// a number as the value it is exact in
+enum Number<'a> {
+    Int(i64),
+    Float(f64),
+    Decimal(&'a BigDecimal),
+}
+
+fn number(value: &Value) -> Option<Number<'_>> {
+    match *value {
+        Value::Tinyint(v) => Some(Number::Int(i64::from(v))),
+        Value::Smallint(v) => Some(Number::Int(i64::from(v))),
+        Value::Int(v) => Some(Number::Int(i64::from(v))),
+        Value::Bigint(v) => Some(Number::Int(v)),
+        Value::Float(v) => Some(Number::Float(f64::from(v))),
+        Value::Double(v) => Some(Number::Float(v)),
+        Value::BigDecimal(ref v) => Some(Number::Decimal(v)),
+        _ => None,
+    }
+}
+
+fn number_rank(value: &Value) -> u8 {
+    match *value {
+        Value::Tinyint(_) => 0,
+        Value::Smallint(_) => 1,
+        Value::Int(_) => 2,
+        Value::Bigint(_) => 3,
+        Value::BigDecimal(_) => 4,
+        Value::Float(_) => 5,
+        _ => 6,
+    }
+}
+
+/// the ints and decimals are compared without going through f64,
+/// which can not hold the integers above 2^53
+fn compare_exact(a: &Number, b: &Number) -> Ordering {
+    match (a, b) {
+        (Number::Int(a), Number::Int(b)) => a.cmp(b),
+        (Number::Float(a), Number::Float(b)) => compare_floats(*a, *b),
+        (Number::Decimal(a), Number::Decimal(b)) => a.cmp(b),
+        (Number::Int(a), Number::Float(b)) => compare_int_float(*a, *b),
+        (Number::Float(a), Number::Int(b)) => compare_int_float(*b, *a).reverse(),
+        (Number::Int(a), Number::Decimal(b)) => BigDecimal::from(*a).cmp(b),
+        (Number::Decimal(a), Number::Int(b)) => (*a).cmp(&BigDecimal::from(*b)),
+        (Number::Decimal(a), Number::Float(b)) => compare_decimal_float(a, *b),
+        (Number::Float(a), Number::Decimal(b)) => compare_decimal_float(b, *a).reverse(),
+    }
+}
+
+/// NaN is greater than everything, -0.0 and 0.0 are equal
+fn compare_floats(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// the integer part of the float is compared as an i64, then its fraction
+fn compare_int_float(a: i64, b: f64) -> Ordering {
+    // 2^63, the first float above i64::MAX
+    const I64_END: f64 = 9_223_372_036_854_775_808.0;
+    if b.is_nan() || b >= I64_END {
+        Ordering::Less
+    } else if b < -I64_END {
+        Ordering::Greater
+    } else {
+        let whole = b.trunc();
+        a.cmp(&(whole as i64)).then_with(|| compare_floats(whole, b))
+    }
+}
+
+/// the finite float is written out in all of its digits, which is its exact decimal
+fn compare_decimal_float(a: &BigDecimal, b: f64) -> Ordering {
+    if b.is_nan() || b == f64::INFINITY {
+        return Ordering::Less;
+    }
+    if b == f64::NEG_INFINITY {
+        return Ordering::Greater;
+    }
+    // the smallest subnormal has 1074 digits after the point
+    match format!("{:.1074}", b).parse::<BigDecimal>() {
+        Ok(b) => a.cmp(&b),
+        Err(_) => Ordering::Equal,
+    }
+}
+
+/// A row borrowed from the `Rows`, its values are looked up by column name
+/// without building a `Dao`
+#[derive(Debug, Clone, Copy)]
+pub struct Row<'a> {
+    columns: &'a [String],
+    values: &'a [Value],
+}
+
+impl<'a> Row<'a> {
+    pub fn columns(&self) -> &'a [String] {
+        self.columns
+    }
+
+    pub fn values(&self) -> &'a [Value] {
+        self.values
+    }
+
+    pub fn get_value(&self, column: &str) -> Option<&'a Value> {
+        self.columns
+            .iter()
+            .position(|c| c == column)
+            .and_then(|i| self.values.get(i))
+    }
+
+    pub fn get<T>(&self, column: &str) -> Result<T, DaoError<'a, T>>
+    where
+        T: TryFrom<&'a Value>,
+        T::Error: Debug,
+    {
+        match self.get_value(column) {
+            Some(v) => TryFrom::try_from(v).map_err(DaoError::ConvertError),
+            None => Err(DaoError::NoSuchValueError(column.into())),
+        }
+    }
+
+    pub fn to_dao(&self) -> Dao {
+        let mut dao = Dao::new();
+        for (column, value) in self.columns.iter().zip(self.values.iter()) {
+            dao.insert_value(column, value);
+        }
+        dao
+    }
+}
+
+/// An iterator over the borrowed `Row`s.
+pub struct RowIter<'a> {
+    columns: &'a [String],
+    iter: slice::Iter<'a, Vec<Value>>,
+}
+
+impl<'a> Iterator for RowIter<'a> {
+    type Item = Row<'a>;
+
+    fn next(&mut self) -> Option<Row<'a>> {
+        let columns = self.columns;
+        self.iter.next().map(|values| Row { columns, values })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
 }
 
+impl<'a> ExactSizeIterator for RowIter<'a> {}
+
 /// An iterator over `Row`s.
 pub struct Iter<'a> {
-    columns: Vec<String>,
+    columns: &'a [String],
     iter: slice::Iter<'a, Vec<Value>>,
 }
 
 impl<'a> Iterator for Iter<'a> {
     type Item = Dao;
 
+    /// an empty row is an empty dao, it does not end the iteration
     fn next(&mut self) -> Option<Dao> {
-        let next_row = self.iter.next();
-        if let Some(row) = next_row {
-            if !row.is_empty() {
-                let mut dao = Dao::new();
-                for (i, column) in self.columns.iter().enumerate() {
-                    if let Some(value) = row.get(i) {
-                        dao.insert(column, value.clone());
-                    }
-                }
-                Some(dao)
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+        let columns = self.columns;
+        self.iter.next().map(|values| Row { columns, values }.to_dao())
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -143,4 +490,117 @@ mod test {
         assert_eq!(dao2, iter.next().unwrap());
     }
 
+    #[test]
+    fn empty_row_does_not_end_iteration() {
+        let rows = Rows {
+            columns: vec!["id".to_string()],
+            data: vec![vec![1.into()], vec![], vec![3.into()]],
+            count: None,
+        };
+        assert_eq!(3, rows.iter().count());
+        assert_eq!(Dao::new(), rows.iter().nth(1).unwrap());
+    }
+
+    #[test]
+    fn cell_access() {
+        let columns = vec!["id".to_string(), "username".to_string()];
+        let data: Vec<Vec<Value>> = vec![
+            vec![1.into(), "ivanceras".into()],
+            vec![2.into(), "lee".into()],
+        ];
+        let rows = Rows {
+            columns,
+            data,
+            count: None,
+        };
+        assert_eq!(rows.get_value(1, 0), Some(&Value::Int(2)));
+        assert_eq!(rows.get_value_by_name(0, "username"), Some(&"ivanceras".into()));
+        assert_eq!(rows.get::<String>(1, "username").unwrap(), "lee");
+        assert!(rows.get::<i32>(2, "id").is_err());
+        assert_eq!(rows.column::<i64>("id").unwrap(), vec![1, 2]);
+        assert!(rows.column::<i64>("email").is_err());
+        let row = rows.row(0).unwrap();
+        assert_eq!(row.get::<String>("username").unwrap(), "ivanceras");
+        assert_eq!(
+            rows.rows().map(|r| r.get::<i32>("id").unwrap()).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn sort_and_filter() {
+        let columns = vec!["id".to_string(), "rate".to_string()];
+        let data: Vec<Vec<Value>> = vec![
+            vec![1.into(), Value::Nil],
+            vec![2.into(), Value::Double(2.5)],
+            vec![3.into(), Value::Int(1)],
+        ];
+        let mut rows = Rows {
+            columns,
+            data,
+            count: None,
+        };
+        rows.sort_by_column("rate");
+        assert_eq!(rows.column::<i32>("id").unwrap(), vec![3, 2, 1]);
+        rows.sort_by_column_desc("rate");
+        assert_eq!(rows.column::<i32>("id").unwrap(), vec![1, 2, 3]);
+        let rated = rows.filter_by_column("rate", |v| !v.is_nil());
+        assert_eq!(rated.column::<i32>("id").unwrap(), vec![2, 3]);
+        rows.retain(|row| row.get::<i32>("id").unwrap() > 1);
+        assert_eq!(rows.len(), 2);
+    }
+
+    #[test]
+    fn sort_nan_and_mixed_types() {
+        let columns = vec!["id".to_string(), "v".to_string()];
+        let data: Vec<Vec<Value>> = vec![
+            vec![1.into(), Value::Double(f64::NAN)],
+            vec![2.into(), Value::Text("b".into())],
+            vec![3.into(), Value::Nil],
+            vec![4.into(), Value::Double(1.5)],
+            vec![5.into(), Value::Bool(true)],
+            vec![6.into(), Value::Bigint(1)],
+            vec![7.into(), Value::Text("a".into())],
+            vec![8.into(), Value::Double(f64::NEG_INFINITY)],
+        ];
+        let mut rows = Rows {
+            columns,
+            data,
+            count: None,
+        };
+        rows.sort_by_column("v");
+        assert_eq!(rows.column::<i32>("id").unwrap(), vec![5, 8, 6, 4, 1, 7, 2, 3]);
+        let big = Value::Bigint((1 << 53) + 1);
+        let approx = Value::Double((1u64 << 53) as f64);
+        assert_eq!(compare(Some(&big), Some(&approx)), Ordering::Greater);
+        assert_eq!(compare(Some(&approx), Some(&big)), Ordering::Less);
+    }
+
+    #[test]
+    fn compare_exact_numbers() {
+        let two_53: i64 = 1 << 53;
+        let cmp = |a: &Value, b: &Value| compare(Some(a), Some(b));
+        // 2^53 + 3 is 2^53 + 4 as f64
+        let bigint = Value::Bigint(two_53 + 3);
+        let double = Value::Double((two_53 + 4) as f64);
+        assert_eq!(cmp(&bigint, &double), Ordering::Less);
+        assert_eq!(cmp(&double, &bigint), Ordering::Greater);
+        let next = Value::Bigint(two_53 + 4);
+        assert_eq!(cmp(&bigint, &next), Ordering::Less);
+        let max = Value::Bigint(i64::MAX);
+        assert_eq!(cmp(&max, &Value::Double(i64::MAX as f64)), Ordering::Less);
+        assert_eq!(cmp(&Value::Int(-2), &Value::Double(-1.5)), Ordering::Less);
+        assert_eq!(cmp(&Value::Int(-1), &Value::Double(-1.5)), Ordering::Greater);
+        let decimal = |v: &str| Value::BigDecimal(v.parse().unwrap());
+        assert_eq!(cmp(&decimal("9007199254740995"), &double), Ordering::Less);
+        // equal, the decimal is after the bigint
+        assert_eq!(cmp(&decimal("9007199254740995"), &bigint), Ordering::Greater);
+        // 0.1 as f64 is slightly above 0.1
+        assert_eq!(cmp(&decimal("0.1"), &Value::Double(0.1)), Ordering::Less);
+        // the ties are broken by the type, then -0.0 before 0.0
+        assert_eq!(cmp(&Value::Int(1), &Value::Double(1.0)), Ordering::Less);
+        assert_eq!(cmp(&Value::Double(-0.0), &Value::Double(0.0)), Ordering::Less);
+        let nan = Value::Double(f64::NAN);
+        assert_eq!(cmp(&nan, &Value::Double(f64::INFINITY)), Ordering::Greater);
+    }
 }
@@ -0,0 +1,1003 @@
+//! Serialize any serde type into a Dao or a Value, without the ToDao derive.
+//! Primitives are mapped to their corresponding Value, sequences of text, int or float
+//! to an Array and the other nested types are stored as json.
+use serde::ser::{self, Impossible, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt::Display;
+use crate::error::ConvertError;
+use crate::value::Array;
+use crate::{Dao, Value};
+
+/// serialize t into a dao, t has to be a struct or a map
+/// ```rust
+/// use serde::Serialize;
+/// use wumn_dao::{to_dao, Value};
+///
+/// #[derive(Serialize)]
+/// struct Actor {
+///     actor_id: i32,
+///     #[serde(rename = "first_name")]
+///     name: String,
+/// }
+///
+/// let dao = to_dao(&Actor { actor_id: 1, name: "PENELOPE".into() }).unwrap();
+/// assert_eq!(dao.get_value("actor_id"), Some(&Value::Int(1)));
+/// assert_eq!(dao.get_value("first_name"), Some(&Value::Text("PENELOPE".into())));
+/// ```
+pub fn to_dao<T>(t: &T) -> Result<Dao, ConvertError>
+where
+    T: Serialize + ?Sized,
+{
+    t.serialize(DaoSerializer)
+}
+
+/// serialize t into a single value
+pub fn to_value<T>(t: &T) -> Result<Value, ConvertError>
+where
+    T: Serialize + ?Sized,
+{
+    t.serialize(ValueSerializer)
+}
+
+impl ser::Error for ConvertError {
+    fn custom<T: Display>(msg: T) -> Self {
+        ConvertError::SerdeError(msg.to_string())
+    }
+}
+
+fn to_json<T>(t: &T) -> Result<serde_json::Value, ConvertError>
+where
+    T: Serialize + ?Sized,
+{
+    serde_json::to_value(t).map_err(ConvertError::JsonError)
+}
+
+fn not_a_dao(what: &str) -> ConvertError {
+    ConvertError::NotSupported(what.to_string(), "Dao".to_string())
+}
+
+/// the key of a map entry in the dao has to be a text
+fn to_key<T>(key: &T) -> Result<String, ConvertError>
+where
+    T: Serialize + ?Sized,
+{
+    match to_value(key)? {
+        Value::Text(v) => Ok(v),
+        Value::Char(v) => Ok(v.to_string()),
+        v => Err(ConvertError::NotSupported(
+            format!("{:?}", v),
+            "column name".to_string(),
+        )),
+    }
+}
+
+struct DaoSerializer;
+
+impl Serializer for DaoSerializer {
+    type Ok = Dao;
+    type Error = ConvertError;
+    type SerializeSeq = Impossible<Dao, ConvertError>;
+    type SerializeTuple = Impossible<Dao, ConvertError>;
+    type SerializeTupleStruct = Impossible<Dao, ConvertError>;
+    type SerializeTupleVariant = Impossible<Dao, ConvertError>;
+    type SerializeMap = DaoMapSerializer;
+    type SerializeStruct = DaoMapSerializer;
+    type SerializeStructVariant = Impossible<Dao, ConvertError>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("bool"))
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("i8"))
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("i16"))
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("i32"))
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("i64"))
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("u8"))
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("u16"))
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("u32"))
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("u64"))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("f64"))
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("char"))
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("str"))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("None"))
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Dao, ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Dao, ConvertError> {
+        Err(not_a_dao("unit"))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Dao, ConvertError> {
+        Err(not_a_dao(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Dao, ConvertError> {
+        Err(not_a_dao(variant))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Dao, ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _value: &T,
+    ) -> Result<Dao, ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(not_a_dao(variant))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, ConvertError> {
+        Err(not_a_dao("sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, ConvertError> {
+        Err(not_a_dao("tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, ConvertError> {
+        Err(not_a_dao(name))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, ConvertError> {
+        Err(not_a_dao(variant))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, ConvertError> {
+        Ok(DaoMapSerializer::default())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, ConvertError> {
+        Ok(DaoMapSerializer::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, ConvertError> {
+        Err(not_a_dao(variant))
+    }
+}
+
+#[derive(Default)]
+struct DaoMapSerializer {
+    dao: Dao,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for DaoMapSerializer {
+    type Ok = Dao;
+    type Error = ConvertError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.next_key = Some(to_key(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| ConvertError::SerdeError("value serialized before its key".into()))?;
+        self.dao.insert_value(key, &to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Dao, ConvertError> {
+        Ok(self.dao)
+    }
+}
+
+impl ser::SerializeStruct for DaoMapSerializer {
+    type Ok = Dao;
+    type Error = ConvertError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.dao.insert_value(key, &to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Dao, ConvertError> {
+        Ok(self.dao)
+    }
+}
+
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = ConvertError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = JsonSerializer;
+    type SerializeMap = JsonSerializer;
+    type SerializeStruct = JsonSerializer;
+    type SerializeStructVariant = JsonSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, ConvertError> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, ConvertError> {
+        Ok(Value::Tinyint(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, ConvertError> {
+        Ok(Value::Smallint(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, ConvertError> {
+        Ok(Value::Int(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, ConvertError> {
+        Ok(Value::Bigint(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, ConvertError> {
+        Ok(Value::Smallint(i16::from(v)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, ConvertError> {
+        Ok(Value::Int(i32::from(v)))
+    }
+
+    /// the narrowest int that holds it, an int column rejects a bigint
+    fn serialize_u32(self, v: u32) -> Result<Value, ConvertError> {
+        match i32::try_from(v) {
+            Ok(v) => Ok(Value::Int(v)),
+            Err(_) => Ok(Value::Bigint(i64::from(v))),
+        }
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, ConvertError> {
+        i64::try_from(v)
+            .map(Value::Bigint)
+            .map_err(|_| ConvertError::NotSupported(v.to_string(), "i64".to_string()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, ConvertError> {
+        Ok(Value::Float(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, ConvertError> {
+        Ok(Value::Double(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, ConvertError> {
+        Ok(Value::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, ConvertError> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, ConvertError> {
+        Ok(Value::Blob(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value, ConvertError> {
+        Ok(Value::Nil)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Value, ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, ConvertError> {
+        Ok(Value::Nil)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, ConvertError> {
+        Ok(Value::Nil)
+    }
+
+    /// unit variants are stored as their name, so they can be used with text and enum columns
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, ConvertError> {
+        Ok(Value::Text(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut map = serde_json::Map::new();
+        map.insert(variant.to_string(), to_json(value)?);
+        Ok(Value::Json(serde_json::Value::Object(map)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer, ConvertError> {
+        Ok(SeqSerializer::with_capacity(len.unwrap_or(0)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, ConvertError> {
+        Ok(SeqSerializer::with_capacity(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, ConvertError> {
+        Ok(SeqSerializer::with_capacity(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<JsonSerializer, ConvertError> {
+        Ok(JsonSerializer::variant(variant, serde_json::Value::Array(vec![])))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<JsonSerializer, ConvertError> {
+        Ok(JsonSerializer::object())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<JsonSerializer, ConvertError> {
+        Ok(JsonSerializer::object())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<JsonSerializer, ConvertError> {
+        Ok(JsonSerializer::variant(
+            variant,
+            serde_json::Value::Object(serde_json::Map::new()),
+        ))
+    }
+}
+
+/// Sequences of text, int or float become an Array, a sequence of u8 such as a `Vec<u8>`
+/// is a Blob and the other sequences are stored as json.
+/// An empty sequence becomes an empty text array.
+struct SeqSerializer {
+    values: Vec<Value>,
+    json: Vec<serde_json::Value>,
+    /// the elements while they are all u8
+    bytes: Option<Vec<u8>>,
+}
+
+impl SeqSerializer {
+    fn with_capacity(len: usize) -> Self {
+        SeqSerializer {
+            values: Vec::with_capacity(len),
+            json: Vec::with_capacity(len),
+            bytes: Some(Vec::with_capacity(len)),
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        if let Some(ref mut bytes) = self.bytes {
+            match value.serialize(ByteSerializer) {
+                Ok(byte) => bytes.push(byte),
+                Err(_) => self.bytes = None,
+            }
+        }
+        self.values.push(to_value(value)?);
+        self.json.push(to_json(value)?);
+        Ok(())
+    }
+
+    fn into_value(self) -> Value {
+        match self.bytes {
+            Some(bytes) if !bytes.is_empty() => return Value::Blob(bytes),
+            _ => (),
+        }
+        let all = |f: fn(&Value) -> bool| self.values.iter().all(f);
+        if all(|v| matches!(v, Value::Text(_))) {
+            Value::Array(Array::Text(
+                self.values
+                    .into_iter()
+                    .filter_map(|v| match v {
+                        Value::Text(v) => Some(v),
+                        _ => None,
+                    })
+                    .collect(),
+            ))
+        } else if all(|v| matches!(v, Value::Int(_))) {
+            Value::Array(Array::Int(
+                self.values
+                    .into_iter()
+                    .filter_map(|v| match v {
+                        Value::Int(v) => Some(v),
+                        _ => None,
+                    })
+                    .collect(),
+            ))
+        } else if all(|v| matches!(v, Value::Float(_))) {
+            Value::Array(Array::Float(
+                self.values
+                    .into_iter()
+                    .filter_map(|v| match v {
+                        Value::Float(v) => Some(v),
+                        _ => None,
+                    })
+                    .collect(),
+            ))
+        } else {
+            Value::Json(serde_json::Value::Array(self.json))
+        }
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = ConvertError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ConvertError> {
+        Ok(self.into_value())
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = ConvertError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ConvertError> {
+        Ok(self.into_value())
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = ConvertError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ConvertError> {
+        Ok(self.into_value())
+    }
+}
+
+/// Accepts only a u8, to tell the sequences of bytes from the other sequences
+struct ByteSerializer;
+
+fn not_a_byte(what: &str) -> ConvertError {
+    ConvertError::NotSupported(what.to_string(), "u8".to_string())
+}
+
+impl Serializer for ByteSerializer {
+    type Ok = u8;
+    type Error = ConvertError;
+    type SerializeSeq = Impossible<u8, ConvertError>;
+    type SerializeTuple = Impossible<u8, ConvertError>;
+    type SerializeTupleStruct = Impossible<u8, ConvertError>;
+    type SerializeTupleVariant = Impossible<u8, ConvertError>;
+    type SerializeMap = Impossible<u8, ConvertError>;
+    type SerializeStruct = Impossible<u8, ConvertError>;
+    type SerializeStructVariant = Impossible<u8, ConvertError>;
+
+    fn serialize_u8(self, v: u8) -> Result<u8, ConvertError> {
+        Ok(v)
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<u8, ConvertError> {
+        Err(not_a_byte("bool"))
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<u8, ConvertError> {
+        Err(not_a_byte("i8"))
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<u8, ConvertError> {
+        Err(not_a_byte("i16"))
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<u8, ConvertError> {
+        Err(not_a_byte("i32"))
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<u8, ConvertError> {
+        Err(not_a_byte("i64"))
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<u8, ConvertError> {
+        Err(not_a_byte("u16"))
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<u8, ConvertError> {
+        Err(not_a_byte("u32"))
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<u8, ConvertError> {
+        Err(not_a_byte("u64"))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<u8, ConvertError> {
+        Err(not_a_byte("f32"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<u8, ConvertError> {
+        Err(not_a_byte("f64"))
+    }
+
+    fn serialize_char(self, _v: char) -> Result<u8, ConvertError> {
+        Err(not_a_byte("char"))
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<u8, ConvertError> {
+        Err(not_a_byte("str"))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<u8, ConvertError> {
+        Err(not_a_byte("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<u8, ConvertError> {
+        Err(not_a_byte("None"))
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<u8, ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(not_a_byte("Some"))
+    }
+
+    fn serialize_unit(self) -> Result<u8, ConvertError> {
+        Err(not_a_byte("unit"))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<u8, ConvertError> {
+        Err(not_a_byte(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<u8, ConvertError> {
+        Err(not_a_byte(name))
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, _value: &T) -> Result<u8, ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(not_a_byte(name))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<u8, ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(not_a_byte(name))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, ConvertError> {
+        Err(not_a_byte("seq"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, ConvertError> {
+        Err(not_a_byte("tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, ConvertError> {
+        Err(not_a_byte(name))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, ConvertError> {
+        Err(not_a_byte(name))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, ConvertError> {
+        Err(not_a_byte("map"))
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, ConvertError> {
+        Err(not_a_byte(name))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, ConvertError> {
+        Err(not_a_byte(name))
+    }
+}
+
+/// Builds the json of the maps, structs and enum variants with content,
+/// which are stored as `Value::Json`
+struct JsonSerializer {
+    /// the variant name, for the enum variants
+    variant: Option<String>,
+    json: serde_json::Value,
+    next_key: Option<String>,
+}
+
+impl JsonSerializer {
+    fn object() -> Self {
+        JsonSerializer {
+            variant: None,
+            json: serde_json::Value::Object(serde_json::Map::new()),
+            next_key: None,
+        }
+    }
+
+    fn variant(variant: &str, json: serde_json::Value) -> Self {
+        JsonSerializer {
+            variant: Some(variant.to_string()),
+            json,
+            next_key: None,
+        }
+    }
+
+    fn insert<T>(&mut self, key: String, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = to_json(value)?;
+        if let serde_json::Value::Object(ref mut map) = self.json {
+            map.insert(key, value);
+        }
+        Ok(())
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = to_json(value)?;
+        if let serde_json::Value::Array(ref mut array) = self.json {
+            array.push(value);
+        }
+        Ok(())
+    }
+
+    fn into_value(self) -> Value {
+        match self.variant {
+            Some(variant) => {
+                let mut map = serde_json::Map::new();
+                map.insert(variant, self.json);
+                Value::Json(serde_json::Value::Object(map))
+            }
+            None => Value::Json(self.json),
+        }
+    }
+}
+
+impl ser::SerializeMap for JsonSerializer {
+    type Ok = Value;
+    type Error = ConvertError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.next_key = match to_json(key)? {
+            serde_json::Value::String(key) => Some(key),
+            key => Some(key.to_string()),
+        };
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| ConvertError::SerdeError("value serialized before its key".into()))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Value, ConvertError> {
+        Ok(self.into_value())
+    }
+}
+
+impl ser::SerializeStruct for JsonSerializer {
+    type Ok = Value;
+    type Error = ConvertError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Value, ConvertError> {
+        Ok(self.into_value())
+    }
+}
+
+impl ser::SerializeStructVariant for JsonSerializer {
+    type Ok = Value;
+    type Error = ConvertError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Value, ConvertError> {
+        Ok(self.into_value())
+    }
+}
+
+impl ser::SerializeTupleVariant for JsonSerializer {
+    type Ok = Value;
+    type Error = ConvertError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), ConvertError>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ConvertError> {
+        Ok(self.into_value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::de::from_dao;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Circle { radius: f64 },
+        Square(f64),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Audit {
+        created_by: Option<String>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Drawing {
+        id: u32,
+        name: String,
+        tags: Vec<String>,
+        points: Vec<i32>,
+        shape: Shape,
+        attributes: BTreeMap<String, bool>,
+        #[serde(flatten)]
+        audit: Audit,
+    }
+
+    #[test]
+    fn serialize_and_back() {
+        let drawing = Drawing {
+            id: 7,
+            name: "house".into(),
+            tags: vec!["sketch".into()],
+            points: vec![1, 2],
+            shape: Shape::Circle { radius: 1.5 },
+            attributes: BTreeMap::new(),
+            audit: Audit { created_by: None },
+        };
+        let dao = to_dao(&drawing).unwrap();
+        assert_eq!(dao.get_value("id"), Some(&Value::Int(7)));
+        assert_eq!(
+            dao.get_value("tags"),
+            Some(&Value::Array(Array::Text(vec!["sketch".into()])))
+        );
+        assert_eq!(dao.get_value("points"), Some(&Value::Array(Array::Int(vec![1, 2]))));
+        assert_eq!(
+            dao.get_value("shape"),
+            Some(&Value::Json(serde_json::json!({"Circle": {"radius": 1.5}})))
+        );
+        assert_eq!(dao.get_value("created_by"), Some(&Value::Nil));
+        let back: Drawing = from_dao(&dao).unwrap();
+        assert_eq!(drawing, back);
+    }
+
+    #[test]
+    fn u32_into_the_narrowest_int() {
+        assert_eq!(to_value(&7u32).unwrap(), Value::Int(7));
+        assert_eq!(to_value(&u32::MAX).unwrap(), Value::Bigint(i64::from(u32::MAX)));
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Attachment {
+        name: String,
+        content: Vec<u8>,
+    }
+
+    #[test]
+    fn bytes_into_blob() {
+        let attachment = Attachment {
+            name: "logo.png".into(),
+            content: vec![0x89, 0x50, 0x4e, 0x47],
+        };
+        let dao = to_dao(&attachment).unwrap();
+        assert_eq!(
+            dao.get_value("content"),
+            Some(&Value::Blob(vec![0x89, 0x50, 0x4e, 0x47]))
+        );
+        let back: Attachment = from_dao(&dao).unwrap();
+        assert_eq!(attachment, back);
+        assert_eq!(to_value(&[1u8, 2][..]).unwrap(), Value::Blob(vec![1, 2]));
+        assert_eq!(to_value(&vec![1u16, 2]).unwrap(), Value::Array(Array::Int(vec![1, 2])));
+    }
+
+    #[test]
+    fn only_struct_and_map_into_dao() {
+        assert!(to_dao(&1).is_err());
+        assert!(to_dao(&vec![1, 2]).is_err());
+        let mut map = BTreeMap::new();
+        map.insert("a", 1i64);
+        let dao = to_dao(&map).unwrap();
+        assert_eq!(dao.get_value("a"), Some(&Value::Bigint(1)));
+    }
+}
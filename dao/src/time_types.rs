@@ -0,0 +1,271 @@
+//! Conversions of the types of the `time` crate, stored in the same variants as their
+//! chrono equivalent so they can be used interchangeably in the entities.
+use crate::error::ConvertError;
+use crate::value::{ToValue, TryToValue};
+use crate::{Interval, Value};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use std::convert::TryFrom;
+use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+const MICROSECONDS_PER_DAY: i64 = 86_400_000_000;
+
+fn to_naive_date(date: Date) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(
+        date.year(),
+        u32::from(u8::from(date.month())),
+        u32::from(date.day()),
+    )
+}
+
+fn to_naive_time(time: Time) -> Option<NaiveTime> {
+    NaiveTime::from_hms_nano_opt(
+        u32::from(time.hour()),
+        u32::from(time.minute()),
+        u32::from(time.second()),
+        time.nanosecond(),
+    )
+}
+
+fn to_date(date: NaiveDate) -> Option<Date> {
+    let month = Month::try_from(u8::try_from(date.month()).ok()?).ok()?;
+    Date::from_calendar_date(date.year(), month, u8::try_from(date.day()).ok()?).ok()
+}
+
+/// the leap second of chrono is the last nanosecond of the second in time
+fn to_time(time: NaiveTime) -> Option<Time> {
+    Time::from_hms_nano(
+        u8::try_from(time.hour()).ok()?,
+        u8::try_from(time.minute()).ok()?,
+        u8::try_from(time.second()).ok()?,
+        time.nanosecond().min(999_999_999),
+    )
+    .ok()
+}
+
+fn to_naive_date_time(date_time: PrimitiveDateTime) -> Option<NaiveDateTime> {
+    Some(to_naive_date(date_time.date())?.and_time(to_naive_time(date_time.time())?))
+}
+
+fn to_utc(date_time: OffsetDateTime) -> Option<DateTime<Utc>> {
+    let utc = date_time.to_offset(time::UtcOffset::UTC);
+    to_naive_date_time(PrimitiveDateTime::new(utc.date(), utc.time())).map(|v| v.and_utc())
+}
+
+fn out_of_range<T: std::fmt::Display>(value: T) -> ConvertError {
+    ConvertError::NotSupported(value.to_string(), "the range of chrono".into())
+}
+
+/// the years of time with the large-dates feature go beyond the range of chrono,
+/// they are clamped here as `From` can not fail. The derived `ToDao::try_to_dao`
+/// used when the entity is saved converts them with `TryToValue`, returning the error.
+fn clamp<T>(year: i32, min: T, max: T) -> T {
+    if year < 0 {
+        min
+    } else {
+        max
+    }
+}
+
+fn to_value_of_time<T, F>(value: &Value, ty: &str, convert: F) -> Result<T, ConvertError>
+where
+    F: FnOnce() -> Option<T>,
+{
+    convert().ok_or_else(|| ConvertError::NotSupported(format!("{:?}", value), ty.into()))
+}
+
+macro_rules! impl_from_time {
+    ($ty:ty, |$v:ident| $convert:expr) => {
+        impl From<$ty> for Value {
+            fn from($v: $ty) -> Self {
+                $convert
+            }
+        }
+
+        impl<'a> From<&'a $ty> for Value {
+            fn from(f: &'a $ty) -> Self {
+                From::from(*f)
+            }
+        }
+
+        impl<'a> From<&'a Option<$ty>> for Value {
+            fn from(f: &'a Option<$ty>) -> Self {
+                match *f {
+                    Some(f) => From::from(f),
+                    None => Value::Nil,
+                }
+            }
+        }
+
+        impl ToValue for $ty {
+            fn to_value(&self) -> Value {
+                self.into()
+            }
+        }
+
+        impl<'a> TryFrom<&'a Value> for Option<$ty> {
+            type Error = ConvertError;
+
+            fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+                match *value {
+                    Value::Nil => Ok(None),
+                    _ => TryFrom::try_from(value).map(Some),
+                }
+            }
+        }
+    };
+}
+
+/// the conversion which fails when the value is out of the range of chrono
+macro_rules! impl_try_to_value {
+    ($ty:ty, |$v:ident| $convert:expr) => {
+        impl TryToValue for $ty {
+            fn try_to_value(&self) -> Result<Value, ConvertError> {
+                let $v = *self;
+                $convert.ok_or_else(|| out_of_range($v))
+            }
+        }
+
+        impl TryToValue for Option<$ty> {
+            fn try_to_value(&self) -> Result<Value, ConvertError> {
+                match *self {
+                    Some(ref v) => v.try_to_value(),
+                    None => Ok(Value::Nil),
+                }
+            }
+        }
+    };
+}
+
+impl_from_time!(OffsetDateTime, |v| Value::Timestamp(
+    to_utc(v).unwrap_or_else(|| clamp(v.year(), DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::MAX_UTC))
+));
+impl_from_time!(PrimitiveDateTime, |v| Value::DateTime(
+    to_naive_date_time(v).unwrap_or_else(|| clamp(v.year(), NaiveDateTime::MIN, NaiveDateTime::MAX))
+));
+impl_from_time!(Date, |v| Value::Date(
+    to_naive_date(v).unwrap_or_else(|| clamp(v.year(), NaiveDate::MIN, NaiveDate::MAX))
+));
+impl_try_to_value!(OffsetDateTime, |v| to_utc(v).map(Value::Timestamp));
+impl_try_to_value!(PrimitiveDateTime, |v| to_naive_date_time(v).map(Value::DateTime));
+impl_try_to_value!(Date, |v| to_naive_date(v).map(Value::Date));
+impl_from_time!(Time, |v| match to_naive_time(v) {
+    Some(time) => Value::Time(time),
+    None => unreachable!("{} is a valid time", v),
+});
+// the microseconds are saturated to the range of an interval
+impl_from_time!(Duration, |v| {
+    let microseconds = i64::try_from(v.whole_microseconds()).unwrap_or(if v.is_negative() {
+        i64::MIN
+    } else {
+        i64::MAX
+    });
+    Value::Interval(Interval::new(microseconds, 0, 0))
+});
+
+/// the same values as `DateTime<Utc>`, which can be outside the range of time
+impl<'a> TryFrom<&'a Value> for OffsetDateTime {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        let utc = DateTime::<Utc>::try_from(value)?;
+        to_value_of_time(value, "OffsetDateTime", || {
+            let date_time = PrimitiveDateTime::try_from(&Value::DateTime(utc.naive_utc())).ok()?;
+            Some(date_time.assume_utc())
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for PrimitiveDateTime {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        let naive = NaiveDateTime::try_from(value)?;
+        to_value_of_time(value, "PrimitiveDateTime", || {
+            Some(PrimitiveDateTime::new(
+                to_date(naive.date())?,
+                to_time(naive.time())?,
+            ))
+        })
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for Date {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        let naive = NaiveDate::try_from(value)?;
+        to_value_of_time(value, "Date", || to_date(naive))
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for Time {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        let naive = NaiveTime::try_from(value)?;
+        to_value_of_time(value, "Time", || to_time(naive))
+    }
+}
+
+/// only the intervals without months, since the length of a month varies
+impl<'a> TryFrom<&'a Value> for Duration {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        to_value_of_time(value, "Duration", || match *value {
+            Value::Interval(ref v) if v.months == 0 => {
+                let days = i64::from(v.days).checked_mul(MICROSECONDS_PER_DAY)?;
+                Some(Duration::microseconds(days.checked_add(v.microseconds)?))
+            }
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_round_trip() {
+        let date = Date::from_calendar_date(2018, Month::January, 29).unwrap();
+        let date_time = date.with_hms_milli(9, 58, 20, 500).unwrap();
+        let value: Value = date_time.into();
+        assert_eq!(PrimitiveDateTime::try_from(&value).unwrap(), date_time);
+        let offset = time::UtcOffset::from_hms(8, 0, 0).unwrap();
+        let offset_date_time = date.with_hms(17, 58, 20).unwrap().assume_offset(offset);
+        let value: Value = offset_date_time.into();
+        assert_eq!(
+            value,
+            Value::Timestamp(
+                NaiveDate::from_ymd_opt(2018, 1, 29)
+                    .unwrap()
+                    .and_hms_opt(9, 58, 20)
+                    .unwrap()
+                    .and_utc()
+            )
+        );
+        assert_eq!(OffsetDateTime::try_from(&value).unwrap(), offset_date_time);
+        let value: Value = date.into();
+        assert_eq!(Date::try_from(&value).unwrap(), date);
+        let time = Time::from_hms(9, 58, 20).unwrap();
+        let value: Value = time.into();
+        assert_eq!(Time::try_from(&value).unwrap(), time);
+        let none: Option<Date> = TryFrom::try_from(&Value::Nil).unwrap();
+        assert_eq!(none, None);
+        assert_eq!(date.try_to_value().unwrap(), Value::from(date));
+        assert_eq!(None::<Date>.try_to_value().unwrap(), Value::Nil);
+    }
+
+    #[test]
+    fn duration_from_interval() {
+        let value: Value = Duration::seconds(90).into();
+        assert_eq!(value, Value::Interval(Interval::new(90_000_000, 0, 0)));
+        let day = Value::Interval(Interval::new(1, 1, 0));
+        assert_eq!(
+            Duration::try_from(&day).unwrap(),
+            Duration::days(1) + Duration::microseconds(1)
+        );
+        assert!(Duration::try_from(&Value::Interval(Interval::new(0, 0, 1))).is_err());
+    }
+}
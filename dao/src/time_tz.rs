@@ -0,0 +1,42 @@
+use chrono::{FixedOffset, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A time of the day with its offset from utc, ie: `time with time zone`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimeTz {
+    pub time: NaiveTime,
+    /// the offset in seconds east of utc
+    pub offset: i32,
+}
+
+impl TimeTz {
+    pub fn new(time: NaiveTime, offset: FixedOffset) -> Self {
+        TimeTz {
+            time,
+            offset: offset.local_minus_utc(),
+        }
+    }
+
+    /// None when the offset is out of the range of a day
+    pub fn fixed_offset(&self) -> Option<FixedOffset> {
+        FixedOffset::east_opt(self.offset)
+    }
+}
+
+/// formatted the way postgresql does, ie: `09:58:20.5+08`
+impl fmt::Display for TimeTz {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let offset = self.offset.abs();
+        let (hours, minutes, seconds) = (offset / 3600, offset % 3600 / 60, offset % 60);
+        write!(f, "{}{}{:02}", self.time.format("%H:%M:%S%.f"), sign, hours)?;
+        if minutes != 0 || seconds != 0 {
+            write!(f, ":{:02}", minutes)?;
+        }
+        if seconds != 0 {
+            write!(f, ":{:02}", seconds)?;
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,18 @@
 #![allow(clippy::cast_lossless)]
 
-use chrono::{DateTime, Utc, NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use bigdecimal::BigDecimal;
 use std::convert::TryFrom;
-use geo_types::geometry::Point;
+use std::net::IpAddr;
+use std::str::FromStr;
+use geo_types::geometry::{Geometry, Line, LineString, Point, Polygon, Rect};
 use uuid::Uuid;
+use crate::geometry::{Circle, LineEquation, Spatial};
 use crate::interval::Interval;
+use crate::network::{IpNetwork, MacAddr};
+use crate::range::Range;
+use crate::time_tz::TimeTz;
 use crate::error::ConvertError;
 
 /// Generic value storage 32 byte in size
@@ -30,16 +36,33 @@ pub enum Value {
     ImageUri(String),
     Char(char),
     Text(String),
-    Json(String),
+    Json(serde_json::Value),
 
     Uuid(Uuid),
     Date(NaiveDate),
     Time(NaiveTime),
+    TimeTz(TimeTz),
     DateTime(NaiveDateTime),
     Timestamp(DateTime<Utc>),
     Interval(Interval),
 
+    Inet(IpNetwork),
+    Cidr(IpNetwork),
+    MacAddr(MacAddr),
+
     Point(Point<f64>),
+    Line(LineEquation),
+    LineSegment(Line<f64>),
+    Box(Rect<f64>),
+    Path(LineString<f64>),
+    Polygon(Box<Polygon<f64>>),
+    Circle(Circle),
+    Geometry(Box<Spatial>),
+
+    Range(Box<Range<Value>>),
+    Multirange(Vec<Range<Value>>),
+    /// the fields of a composite type in their order
+    Composite(Vec<(String, Value)>),
 
     Array(Array),
 }
@@ -81,6 +104,41 @@ pub trait ToValue {
     fn to_value(&self) -> Value;
 }
 
+/// A field which can fail to convert into a value,
+/// such as a `Json` that can not be serialized
+pub trait TryToValue {
+    fn try_to_value(&self) -> Result<Value, ConvertError>;
+}
+
+/// A rust type stored as a single value of a type defined in the database,
+/// implemented by `#[derive(DbEnum)]` and `#[derive(DbComposite)]`.
+/// Their `Option` is a nullable value.
+pub trait DbValue: Sized {
+    fn to_db_value(&self) -> Value;
+
+    fn from_db_value(value: &Value) -> Result<Self, ConvertError>;
+}
+
+impl<'a, T: DbValue> TryFrom<&'a Value> for Option<T> {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Nil => Ok(None),
+            _ => T::from_db_value(value).map(Some),
+        }
+    }
+}
+
+impl<'a, T: DbValue> From<&'a Option<T>> for Value {
+    fn from(f: &'a Option<T>) -> Self {
+        match *f {
+            Some(ref f) => f.to_db_value(),
+            None => Value::Nil,
+        }
+    }
+}
+
 macro_rules! impl_to_value {
     ($ty:ty) => {
         impl ToValue for $ty {
@@ -168,13 +226,115 @@ impl_from!(i32, Int);
 impl_from!(i64, Bigint);
 impl_from!(f32, Float);
 impl_from!(f64, Double);
+impl_from!(BigDecimal, BigDecimal);
 impl_from!(Vec<u8>, Blob);
 impl_from!(char, Char);
 impl_from!(String, Text);
 impl_from!(Uuid, Uuid);
 impl_from!(NaiveDate, Date);
 impl_from!(NaiveTime, Time);
-impl_from!(DateTime<Utc>, Timestamp);
+impl_from!(NaiveDateTime, DateTime);
+impl_from!(TimeTz, TimeTz);
+impl_from!(serde_json::Value, Json);
+impl_from!(Interval, Interval);
+impl_from!(IpNetwork, Inet);
+impl_from!(MacAddr, MacAddr);
+impl_from!(Point<f64>, Point);
+impl_from!(LineEquation, Line);
+impl_from!(Line<f64>, LineSegment);
+impl_from!(Rect<f64>, Box);
+impl_from!(LineString<f64>, Path);
+impl_from!(Circle, Circle);
+
+macro_rules! impl_from_boxed {
+    ($ty:ty, $variant:ident) => {
+        impl From<$ty> for Value {
+            fn from(f: $ty) -> Self {
+                Value::$variant(Box::new(f.into()))
+            }
+        }
+
+        impl<'a> From<&'a $ty> for Value {
+            fn from(f: &'a $ty) -> Self {
+                Value::$variant(Box::new(f.to_owned().into()))
+            }
+        }
+
+        impl<'a> From<&'a Option<$ty>> for Value {
+            fn from(f: &'a Option<$ty>) -> Self {
+                match *f {
+                    Some(ref f) => From::from(f),
+                    None => Value::Nil,
+                }
+            }
+        }
+
+        impl_to_value!($ty);
+    };
+}
+
+impl_from_boxed!(Polygon<f64>, Polygon);
+impl_from_boxed!(Spatial, Geometry);
+// a geometry without its spatial reference system
+impl_from_boxed!(Geometry<f64>, Geometry);
+
+/// an ip address is a single host
+impl From<IpAddr> for Value {
+    fn from(f: IpAddr) -> Self {
+        Value::Inet(IpNetwork::host(f))
+    }
+}
+
+impl<'a> From<&'a IpAddr> for Value {
+    fn from(f: &'a IpAddr) -> Self {
+        Value::Inet(IpNetwork::host(*f))
+    }
+}
+
+impl<'a> From<&'a Option<IpAddr>> for Value {
+    fn from(f: &'a Option<IpAddr>) -> Self {
+        match *f {
+            Some(ref f) => From::from(f),
+            None => Value::Nil,
+        }
+    }
+}
+
+impl_to_value!(IpAddr);
+
+/// the date time in any timezone is stored in utc, like in a `timestamp with time zone`
+impl<Tz: TimeZone> From<DateTime<Tz>> for Value {
+    fn from(f: DateTime<Tz>) -> Self {
+        Value::Timestamp(f.with_timezone(&Utc))
+    }
+}
+
+impl<'a, Tz: TimeZone> From<&'a DateTime<Tz>> for Value {
+    fn from(f: &'a DateTime<Tz>) -> Self {
+        Value::Timestamp(f.with_timezone(&Utc))
+    }
+}
+
+impl<'a, Tz: TimeZone> From<&'a Option<DateTime<Tz>>> for Value {
+    fn from(f: &'a Option<DateTime<Tz>>) -> Self {
+        match *f {
+            Some(ref f) => From::from(f),
+            None => Value::Nil,
+        }
+    }
+}
+
+impl<Tz: TimeZone> ToValue for DateTime<Tz> {
+    fn to_value(&self) -> Value {
+        self.into()
+    }
+}
+
+impl<Tz: TimeZone> ToValue for &DateTime<Tz> {
+    fn to_value(&self) -> Value {
+        (*self).into()
+    }
+}
 
 impl<'a> From<&'a str> for Value {
     fn from(f: &'a str) -> Value {
@@ -188,38 +348,73 @@ impl ToValue for &str {
     }
 }
 
-impl From<Vec<String>> for Value {
-    fn from(f: Vec<String>) -> Value {
-        Value::Array(Array::Text(f))
-    }
+macro_rules! impl_from_array {
+    ($ty:ty, $variant:ident) => {
+        impl From<Vec<$ty>> for Value {
+            fn from(f: Vec<$ty>) -> Self {
+                Value::Array(Array::$variant(f))
+            }
+        }
+
+        impl<'a> From<&'a Vec<$ty>> for Value {
+            fn from(f: &'a Vec<$ty>) -> Self {
+                Value::Array(Array::$variant(f.to_owned()))
+            }
+        }
+
+        impl<'a> From<&'a Option<Vec<$ty>>> for Value {
+            fn from(f: &'a Option<Vec<$ty>>) -> Self {
+                match *f {
+                    Some(ref f) => From::from(f),
+                    None => Value::Nil,
+                }
+            }
+        }
+    };
 }
 
-impl<'a> From<&'a Vec<String>> for Value {
-    fn from(f: &Vec<String>) -> Value {
-        Value::Array(Array::Text(f.to_owned()))
+impl_from_array!(i32, Int);
+impl_from_array!(f32, Float);
+impl_from_array!(String, Text);
+
+impl ToValue for Vec<i32> {
+    fn to_value(&self) -> Value {
+        self.into()
     }
 }
 
-impl<'a> From<&'a Value> for Vec<String> {
-    fn from(v: &'a Value) -> Vec<String> {
-        match *v {
-            Value::Array(Array::Text(ref t)) => t.to_owned(),
-            _ => panic!("unable to convert {:?} to Vec<String>", v),
-        }
+impl ToValue for Vec<f32> {
+    fn to_value(&self) -> Value {
+        self.into()
     }
 }
 
+fn not_supported(value: &Value, ty: &str) -> ConvertError {
+    ConvertError::NotSupported(format!("{:?}", value), ty.into())
+}
+
+/// The conversions from a value to the rust types.
+/// The variants listed first are widened without loss,
+/// the ones after `checked` are converted when they fit in the type.
 macro_rules! impl_tryfrom {
     ($ty: ty, $ty_name: tt, $($variant: ident),*) => {
+        impl_tryfrom!($ty, $ty_name, $($variant),*; checked);
+    };
+
+    ($ty: ty, $ty_name: tt, $($variant: ident),*; checked $($checked: ident),*) => {
         /// try from to owned
         impl<'a> TryFrom<&'a Value> for $ty {
             type Error = ConvertError;
 
             fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
                 match *value {
-                    $(Value::$variant(ref v) => Ok(v.to_owned() as $ty),
+                    $(Value::$variant(ref v) => Ok(From::from(v.to_owned())),
                     )*
-                    _ => Err(ConvertError::NotSupported(format!("{:?}",value), $ty_name.into())),
+                    $(Value::$checked(v) => {
+                        <$ty>::try_from(v).map_err(|_| not_supported(value, $ty_name))
+                    }
+                    )*
+                    _ => Err(not_supported(value, $ty_name)),
                 }
             }
         }
@@ -243,6 +438,33 @@ macro_rules! impl_tryfrom_option {
     };
 }
 
+/// the bytes of a base64 data uri, ie: `data:image/png;base64,iVBORw0KGgo=`
+pub fn data_uri_bytes(uri: &str) -> Result<Vec<u8>, ConvertError> {
+    let invalid = || {
+        let header = uri.split(',').next().unwrap_or_default();
+        ConvertError::NotSupported(format!("data uri {}", header), "Vec<u8>".into())
+    };
+    let rest = uri.strip_prefix("data:").ok_or_else(invalid)?;
+    let comma = rest.find(',').ok_or_else(invalid)?;
+    if !rest[..comma].ends_with(";base64") {
+        return Err(invalid());
+    }
+    base64::decode_config(&rest[comma + 1..], base64::MIME).map_err(|_| invalid())
+}
+
+/// the image read as a data uri is decoded into its bytes
+impl<'a> TryFrom<&'a Value> for Vec<u8> {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Blob(ref v) => Ok(v.to_owned()),
+            Value::ImageUri(ref v) => data_uri_bytes(v),
+            _ => Err(not_supported(value, "Vec<u8>")),
+        }
+    }
+}
+
 /// Char can be casted into String
 /// and they havea separate implementation for extracting data
 impl<'a> TryFrom<&'a Value> for String {
@@ -251,74 +473,348 @@ impl<'a> TryFrom<&'a Value> for String {
     fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
         match *value {
             Value::Text(ref v) => Ok(v.to_owned()),
+            Value::ImageUri(ref v) => Ok(v.to_owned()),
             Value::Char(ref v) => {
                 let mut s = String::new();
                 s.push(*v);
                 Ok(s)
             }
-            _ => Err(ConvertError::NotSupported(
-                format!("{:?}", value),
-                "String".to_string(),
-            )),
+            _ => Err(not_supported(value, "String")),
         }
     }
 }
 
 impl_tryfrom!(bool, "bool", Bool);
-impl_tryfrom!(i8, "i8", Tinyint);
-impl_tryfrom!(i16, "i16", Tinyint, Smallint);
-impl_tryfrom!(i32, "i32", Tinyint, Smallint, Int, Bigint);
+impl_tryfrom!(i8, "i8", Tinyint; checked Smallint, Int, Bigint);
+impl_tryfrom!(i16, "i16", Tinyint, Smallint; checked Int, Bigint);
+impl_tryfrom!(i32, "i32", Tinyint, Smallint, Int; checked Bigint);
 impl_tryfrom!(i64, "i64", Tinyint, Smallint, Int, Bigint);
-impl_tryfrom!(f32, "f32", Float);
-impl_tryfrom!(f64, "f64", Float, Double);
-impl_tryfrom!(Vec<u8>, "Vec<u8>", Blob);
-impl_tryfrom!(char, "char", Char);
-impl_tryfrom!(Uuid, "Uuid", Uuid);
-impl_tryfrom!(NaiveDate, "NaiveDate", Date);
-
-impl<'a> TryFrom<&'a Value> for NaiveDateTime {
+impl_tryfrom!(Interval, "Interval", Interval);
+impl_tryfrom!(Point<f64>, "Point", Point);
+impl_tryfrom!(LineEquation, "LineEquation", Line);
+impl_tryfrom!(Line<f64>, "Line", LineSegment);
+impl_tryfrom!(Rect<f64>, "Rect", Box);
+impl_tryfrom!(LineString<f64>, "LineString", Path);
+impl_tryfrom!(Circle, "Circle", Circle);
+
+/// a bigint is a double when it is exact, up to 2^53
+impl<'a> TryFrom<&'a Value> for f64 {
     type Error = ConvertError;
 
-    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        const EXACT: i64 = 1 << 53;
+        match *value {
+            Value::Tinyint(v) => Ok(f64::from(v)),
+            Value::Smallint(v) => Ok(f64::from(v)),
+            Value::Int(v) => Ok(f64::from(v)),
+            Value::Bigint(v) if (-EXACT..=EXACT).contains(&v) => Ok(v as f64),
+            Value::Float(v) => Ok(f64::from(v)),
+            Value::Double(v) => Ok(v),
+            _ => Err(not_supported(value, "f64")),
+        }
+    }
+}
+
+/// a double is narrowed when it is in the range of f32, losing its precision
+impl<'a> TryFrom<&'a Value> for f32 {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Tinyint(v) => Ok(f32::from(v)),
+            Value::Smallint(v) => Ok(f32::from(v)),
+            Value::Float(v) => Ok(v),
+            Value::Double(v) if !v.is_finite() || v.abs() <= f64::from(f32::MAX) => Ok(v as f32),
+            _ => Err(not_supported(value, "f32")),
+        }
+    }
+}
+
+/// the text of a single character can be a char
+impl<'a> TryFrom<&'a Value> for char {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Char(v) => Ok(v),
+            Value::Text(ref v) => {
+                let mut chars = v.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(c),
+                    _ => Err(not_supported(value, "char")),
+                }
+            }
+            _ => Err(not_supported(value, "char")),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for BigDecimal {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::BigDecimal(ref v) => Ok(v.to_owned()),
+            Value::Tinyint(v) => Ok(BigDecimal::from(v)),
+            Value::Smallint(v) => Ok(BigDecimal::from(v)),
+            Value::Int(v) => Ok(BigDecimal::from(v)),
+            Value::Bigint(v) => Ok(BigDecimal::from(v)),
+            Value::Float(v) if v.is_finite() => parse(value, &v.to_string(), "BigDecimal"),
+            Value::Double(v) if v.is_finite() => parse(value, &v.to_string(), "BigDecimal"),
+            Value::Text(ref v) => parse(value, v.trim(), "BigDecimal"),
+            _ => Err(not_supported(value, "BigDecimal")),
+        }
+    }
+}
+
+fn parse<T: FromStr>(value: &Value, v: &str, ty: &str) -> Result<T, ConvertError> {
+    v.parse().map_err(|_| not_supported(value, ty))
+}
+
+impl<'a> TryFrom<&'a Value> for Uuid {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Uuid(v) => Ok(v),
+            Value::Text(ref v) => parse(value, v, "Uuid"),
+            _ => Err(not_supported(value, "Uuid")),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for NaiveDate {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Date(v) => Ok(v),
+            Value::Text(ref v) => NaiveDate::parse_from_str(v, "%Y-%m-%d")
+                .map_err(|_| not_supported(value, "NaiveDate")),
+            _ => Err(not_supported(value, "NaiveDate")),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for NaiveTime {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Time(v) => Ok(v),
+            Value::Text(ref v) => NaiveTime::parse_from_str(v, "%H:%M:%S%.f")
+                .map_err(|_| not_supported(value, "NaiveTime")),
+            _ => Err(not_supported(value, "NaiveTime")),
+        }
+    }
+}
+
+/// json stored in a text column is parsed
+impl<'a> TryFrom<&'a Value> for serde_json::Value {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
         match *value {
-            Value::Text(ref v) => Ok(parse_naive_date_time(v)),
-            _ => Err(ConvertError::NotSupported(
-                format!("{:?}", value),
-                "NaiveDateTime".to_string(),
-            )),
+            Value::Json(ref v) => Ok(v.to_owned()),
+            Value::Text(ref v) => serde_json::from_str(v).map_err(ConvertError::JsonError),
+            _ => Err(not_supported(value, "serde_json::Value")),
         }
     }
 }
 
-fn parse_naive_date_time(v: &str) -> NaiveDateTime {
-    let ts = NaiveDateTime::parse_from_str(&v, "%Y-%m-%d %H:%M:%S");
-    if let Ok(ts) = ts {
-        ts
-    } else {
-        let ts = NaiveDateTime::parse_from_str(&v, "%Y-%m-%d %H:%M:%S.%f");
-        if let Ok(ts) = ts {
-            ts
-        } else {
-            panic!("unable to parse timestamp: {}", v);
+/// a date is at midnight and a timestamp is in utc
+impl TryFrom<&Value> for NaiveDateTime {
+    type Error = ConvertError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::DateTime(v) => Ok(v),
+            Value::Timestamp(v) => Ok(v.naive_utc()),
+            Value::Date(v) => Ok(v.and_time(NaiveTime::MIN)),
+            Value::Text(ref v) => parse_naive_date_time(v)
+                .ok_or_else(|| not_supported(value, "NaiveDateTime")),
+            _ => Err(not_supported(value, "NaiveDateTime")),
         }
     }
 }
 
-impl<'a> TryFrom<&'a Value> for DateTime<Utc> {
+fn parse_naive_date_time(v: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(v, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()
+}
+
+/// a date time without timezone is in utc
+impl TryFrom<&Value> for DateTime<Utc> {
     type Error = ConvertError;
 
     fn try_from(value: &Value) -> Result<Self, Self::Error> {
         match *value {
-            Value::Text(ref v) => Ok(DateTime::<Utc>::from_utc(parse_naive_date_time(v), Utc)),
             Value::Timestamp(v) => Ok(v),
-            _ => Err(ConvertError::NotSupported(
-                format!("{:?}", value),
-                "DateTime".to_string(),
-            )),
+            Value::DateTime(v) => Ok(v.and_utc()),
+            Value::Date(v) => Ok(v.and_time(NaiveTime::MIN).and_utc()),
+            Value::Text(ref v) => DateTime::parse_from_rfc3339(v)
+                .or_else(|_| DateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.f%#z"))
+                .map(|v| v.with_timezone(&Utc))
+                .ok()
+                .or_else(|| parse_naive_date_time(v).map(|v| v.and_utc()))
+                .ok_or_else(|| not_supported(value, "DateTime")),
+            _ => Err(not_supported(value, "DateTime")),
+        }
+    }
+}
+
+/// the timestamp keeps its instant, a date time without timezone is in utc
+/// and a text keeps its offset
+impl<'a> TryFrom<&'a Value> for DateTime<FixedOffset> {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Text(ref v) => DateTime::parse_from_rfc3339(v)
+                .or_else(|_| DateTime::parse_from_str(v, "%Y-%m-%d %H:%M:%S%.f%#z"))
+                .map_err(|_| not_supported(value, "DateTime<FixedOffset>")),
+            _ => DateTime::<Utc>::try_from(value)
+                .map(|v| v.fixed_offset())
+                .map_err(|_| not_supported(value, "DateTime<FixedOffset>")),
+        }
+    }
+}
+
+/// the timestamp keeps its instant, a date time without timezone is in the local timezone
+impl<'a> TryFrom<&'a Value> for DateTime<Local> {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::DateTime(ref v) => Local
+                .from_local_datetime(v)
+                .earliest()
+                .ok_or_else(|| not_supported(value, "DateTime<Local>")),
+            _ => DateTime::<Utc>::try_from(value)
+                .map(|v| v.with_timezone(&Local))
+                .map_err(|_| not_supported(value, "DateTime<Local>")),
+        }
+    }
+}
+
+/// a time without timezone is in utc
+impl<'a> TryFrom<&'a Value> for TimeTz {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::TimeTz(v) => Ok(v),
+            Value::Time(v) => Ok(TimeTz { time: v, offset: 0 }),
+            _ => Err(not_supported(value, "TimeTz")),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for IpNetwork {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Inet(v) | Value::Cidr(v) => Ok(v),
+            Value::Text(ref v) => parse(value, v, "IpNetwork"),
+            _ => Err(not_supported(value, "IpNetwork")),
+        }
+    }
+}
+
+/// the address of an inet, without its prefix
+impl<'a> TryFrom<&'a Value> for IpAddr {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Inet(v) => Ok(v.addr),
+            Value::Text(ref v) => parse(value, v.trim(), "IpAddr"),
+            _ => Err(not_supported(value, "IpAddr")),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a Value> for MacAddr {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::MacAddr(v) => Ok(v),
+            Value::Text(ref v) => parse(value, v, "MacAddr"),
+            _ => Err(not_supported(value, "MacAddr")),
+        }
+    }
+}
+
+/// a box is a polygon of its 4 corners
+impl<'a> TryFrom<&'a Value> for Polygon<f64> {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Polygon(ref v) => Ok(v.as_ref().to_owned()),
+            Value::Box(v) => Ok(v.to_polygon()),
+            Value::Geometry(ref v) => match v.geometry {
+                Geometry::Polygon(ref v) => Ok(v.to_owned()),
+                Geometry::Rect(v) => Ok(v.to_polygon()),
+                _ => Err(not_supported(value, "Polygon")),
+            },
+            _ => Err(not_supported(value, "Polygon")),
+        }
+    }
+}
+
+/// the geometric values are also geometries, except for the infinite line and the circle
+impl<'a> TryFrom<&'a Value> for Geometry<f64> {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Geometry(ref v) => Ok(v.geometry.to_owned()),
+            Value::Point(v) => Ok(Geometry::Point(v)),
+            Value::LineSegment(v) => Ok(Geometry::Line(v)),
+            Value::Box(v) => Ok(Geometry::Rect(v)),
+            Value::Path(ref v) => Ok(Geometry::LineString(v.to_owned())),
+            Value::Polygon(ref v) => Ok(Geometry::Polygon(v.as_ref().to_owned())),
+            _ => Err(not_supported(value, "Geometry")),
         }
     }
 }
 
+impl<'a> TryFrom<&'a Value> for Spatial {
+    type Error = ConvertError;
+
+    fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+        match *value {
+            Value::Geometry(ref v) => Ok(v.as_ref().to_owned()),
+            _ => Geometry::try_from(value).map(Spatial::from),
+        }
+    }
+}
+
+macro_rules! impl_tryfrom_array {
+    ($ty:ty, $variant:ident) => {
+        impl<'a> TryFrom<&'a Value> for Vec<$ty> {
+            type Error = ConvertError;
+
+            fn try_from(value: &'a Value) -> Result<Self, Self::Error> {
+                match *value {
+                    Value::Array(Array::$variant(ref v)) => Ok(v.to_owned()),
+                    _ => Err(not_supported(value, concat!("Vec<", stringify!($ty), ">"))),
+                }
+            }
+        }
+
+        impl_tryfrom_option!(Vec<$ty>);
+    };
+}
+
+impl_tryfrom_array!(i32, Int);
+impl_tryfrom_array!(f32, Float);
+impl_tryfrom_array!(String, Text);
+
 impl_tryfrom_option!(bool);
 impl_tryfrom_option!(i8);
 impl_tryfrom_option!(i16);
@@ -326,13 +822,32 @@ impl_tryfrom_option!(i32);
 impl_tryfrom_option!(i64);
 impl_tryfrom_option!(f32);
 impl_tryfrom_option!(f64);
+impl_tryfrom_option!(BigDecimal);
 impl_tryfrom_option!(Vec<u8>);
 impl_tryfrom_option!(char);
 impl_tryfrom_option!(String);
 impl_tryfrom_option!(Uuid);
 impl_tryfrom_option!(NaiveDate);
+impl_tryfrom_option!(NaiveTime);
 impl_tryfrom_option!(NaiveDateTime);
 impl_tryfrom_option!(DateTime<Utc>);
+impl_tryfrom_option!(DateTime<FixedOffset>);
+impl_tryfrom_option!(DateTime<Local>);
+impl_tryfrom_option!(TimeTz);
+impl_tryfrom_option!(Interval);
+impl_tryfrom_option!(IpNetwork);
+impl_tryfrom_option!(IpAddr);
+impl_tryfrom_option!(MacAddr);
+impl_tryfrom_option!(Point<f64>);
+impl_tryfrom_option!(LineEquation);
+impl_tryfrom_option!(Line<f64>);
+impl_tryfrom_option!(Rect<f64>);
+impl_tryfrom_option!(LineString<f64>);
+impl_tryfrom_option!(Polygon<f64>);
+impl_tryfrom_option!(Circle);
+impl_tryfrom_option!(Geometry<f64>);
+impl_tryfrom_option!(Spatial);
+impl_tryfrom_option!(serde_json::Value);
 
 #[cfg(test)]
 mod tests {
@@ -350,6 +865,16 @@ mod tests {
         assert_eq!(16, size_of::<Uuid>());
     }
 
+    #[test]
+    fn image_uri_bytes() {
+        let png = vec![0x89, b'P', b'N', b'G'];
+        let uri = Value::ImageUri("data:image/png;base64,iVBORw==".into());
+        assert_eq!(Vec::<u8>::try_from(&uri).unwrap(), png);
+        assert_eq!(Option::<Vec<u8>>::try_from(&uri).unwrap(), Some(png));
+        assert!(data_uri_bytes("data:text/plain,hello").is_err());
+        assert!(data_uri_bytes("not a uri").is_err());
+    }
+
     #[test]
     fn test_types() {
         let _: Value = 127i8.into();
@@ -389,4 +914,122 @@ mod tests {
         assert!(ts.is_ok());
         DateTime::<Utc>::from_utc(ts.unwrap(), Utc);
     }
+
+    #[test]
+    fn checked_narrowing() {
+        assert_eq!(i16::try_from(&Value::Int(300)).unwrap(), 300);
+        assert!(i16::try_from(&Value::Int(40_000)).is_err());
+        assert!(i32::try_from(&Value::Bigint(i64::MAX)).is_err());
+        assert!(i8::try_from(&Value::Smallint(128)).is_err());
+        assert_eq!(i64::try_from(&Value::Tinyint(-1)).unwrap(), -1);
+        assert_eq!(f32::try_from(&Value::Double(1.5)).unwrap(), 1.5);
+        assert!(f32::try_from(&Value::Double(f64::MAX)).is_err());
+        assert_eq!(f64::try_from(&Value::Int(3)).unwrap(), 3.0);
+        assert_eq!(f64::try_from(&Value::Bigint(3)).unwrap(), 3.0);
+        assert_eq!(f64::try_from(&Value::Bigint(-(1 << 53))).unwrap(), -9_007_199_254_740_992.0);
+        assert!(f64::try_from(&Value::Bigint((1 << 53) + 1)).is_err());
+        assert_eq!(
+            BigDecimal::try_from(&Value::Double(0.25)).unwrap(),
+            "0.25".parse::<BigDecimal>().unwrap()
+        );
+        assert!(BigDecimal::try_from(&Value::Double(f64::NAN)).is_err());
+        assert!(char::try_from(&Value::Text("ab".into())).is_err());
+    }
+
+    #[test]
+    fn date_time_variants() {
+        let date = NaiveDate::from_ymd_opt(2018, 1, 29).unwrap();
+        let date_time = date.and_hms_opt(9, 58, 20).unwrap();
+        assert_eq!(
+            NaiveDateTime::try_from(&Value::DateTime(date_time)).unwrap(),
+            date_time
+        );
+        assert_eq!(
+            NaiveDateTime::try_from(&Value::Timestamp(date_time.and_utc())).unwrap(),
+            date_time
+        );
+        assert_eq!(
+            NaiveDateTime::try_from(&Value::Text("2018-01-29T09:58:20".into())).unwrap(),
+            date_time
+        );
+        assert!(NaiveDateTime::try_from(&Value::Text("yesterday".into())).is_err());
+        assert_eq!(
+            DateTime::<Utc>::try_from(&Value::Text("2018-01-29 10:58:20+01:00".into())).unwrap(),
+            date_time.and_utc()
+        );
+        assert_eq!(
+            NaiveTime::try_from(&Value::Text("09:58:20.5".into())).unwrap(),
+            NaiveTime::from_hms_milli_opt(9, 58, 20, 500).unwrap()
+        );
+        assert_eq!(NaiveDate::try_from(&Value::Text("2018-01-29".into())).unwrap(), date);
+    }
+
+    #[test]
+    fn options_and_compound_types() {
+        let none: Option<Interval> = TryFrom::try_from(&Value::Nil).unwrap();
+        assert_eq!(none, None);
+        let interval = Interval::new(1, 2, 3);
+        let value: Value = (&interval).into();
+        assert_eq!(Interval::try_from(&value).unwrap(), interval);
+        let point = Point::new(1.0, 2.0);
+        assert_eq!(Point::<f64>::try_from(&Value::from(point)).unwrap(), point);
+        let ids: Option<Vec<i32>> = TryFrom::try_from(&Value::from(vec![1, 2])).unwrap();
+        assert_eq!(ids, Some(vec![1, 2]));
+        assert!(Vec::<String>::try_from(&Value::Int(1)).is_err());
+        let uuid: Option<Uuid> =
+            TryFrom::try_from(&Value::Text("67e55044-10b1-426f-9247-bb680e5fe0c8".into())).unwrap();
+        assert!(uuid.is_some());
+    }
+
+    #[test]
+    fn geometries() {
+        let square = Rect::new((0.0, 0.0), (2.0, 2.0));
+        let value: Value = square.into();
+        assert_eq!(Rect::try_from(&value).unwrap(), square);
+        assert_eq!(Polygon::try_from(&value).unwrap(), square.to_polygon());
+        let path = LineString::from(vec![(0.0, 0.0), (1.0, 1.0)]);
+        let value: Value = (&path).into();
+        assert_eq!(Geometry::try_from(&value).unwrap(), Geometry::LineString(path));
+        let spatial = Spatial::new(Geometry::Point(Point::new(1.0, 2.0)), Some(4326));
+        let value: Value = (&spatial).into();
+        assert_eq!(Spatial::try_from(&value).unwrap(), spatial);
+        assert_eq!(spatial.type_name(), "Point");
+        let circle: Option<Circle> = TryFrom::try_from(&Value::Nil).unwrap();
+        assert_eq!(circle, None);
+        assert!(Circle::try_from(&value).is_err());
+    }
+
+    #[test]
+    fn network_addresses() {
+        let addr: IpAddr = "10.1.2.3".parse().unwrap();
+        let value: Value = addr.into();
+        assert_eq!(value, Value::Inet(IpNetwork::host(addr)));
+        assert_eq!(IpAddr::try_from(&value).unwrap(), addr);
+        let cidr = Value::Cidr("10.1.0.0/16".parse().unwrap());
+        assert_eq!(IpNetwork::try_from(&cidr).unwrap().prefix, 16);
+        assert!(IpAddr::try_from(&cidr).is_err());
+        let mac: Option<MacAddr> = TryFrom::try_from(&Value::Text("08:00:2b:01:02:03".into())).unwrap();
+        assert_eq!(mac, Some(MacAddr([8, 0, 0x2b, 1, 2, 3])));
+    }
+
+    #[test]
+    fn timezones() {
+        let offset = FixedOffset::east_opt(8 * 3600).unwrap();
+        let local = offset.with_ymd_and_hms(2018, 1, 29, 17, 58, 20).unwrap();
+        let value: Value = local.into();
+        let utc = Utc.with_ymd_and_hms(2018, 1, 29, 9, 58, 20).unwrap();
+        assert_eq!(value, Value::Timestamp(utc));
+        assert_eq!(DateTime::<FixedOffset>::try_from(&value).unwrap(), local);
+        assert_eq!(DateTime::<Local>::try_from(&value).unwrap(), utc);
+        let text = Value::Text("2018-01-29T17:58:20+08:00".into());
+        assert_eq!(
+            DateTime::<FixedOffset>::try_from(&text).unwrap().offset(),
+            &offset
+        );
+        let time = TimeTz::new(NaiveTime::from_hms_opt(17, 58, 20).unwrap(), offset);
+        assert_eq!(time.to_string(), "17:58:20+08");
+        assert_eq!(TimeTz::try_from(&Value::TimeTz(time)).unwrap(), time);
+        let india = TimeTz::new(time.time, FixedOffset::west_opt(-19800).unwrap());
+        assert_eq!(india.to_string(), "17:58:20+05:30");
+    }
 }
@@ -0,0 +1,57 @@
+//! How the blobs read from the database are returned,
+//! as their bytes or as a data uri which can be shown in a browser.
+use wumn_dao::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlobPolicy {
+    /// the bytes as they are, without detecting their mime type
+    Raw,
+    /// a data uri for the blobs detected as one of these mime types, ie: `image/png`,
+    /// the other blobs are raw
+    DataUri(Vec<String>),
+}
+
+/// the jpeg and png images are data uris
+impl Default for BlobPolicy {
+    fn default() -> Self {
+        BlobPolicy::DataUri(vec!["image/jpeg".to_string(), "image/png".to_string()])
+    }
+}
+
+impl BlobPolicy {
+    pub fn to_value(&self, bytes: Vec<u8>) -> Value {
+        match *self {
+            BlobPolicy::Raw => Value::Blob(bytes),
+            BlobPolicy::DataUri(ref mime_types) => {
+                let mime_type = tree_magic::from_u8(&bytes);
+                if mime_types.contains(&mime_type) {
+                    let base64 = base64::encode_config(&bytes, base64::MIME);
+                    Value::ImageUri(format!("data:{};base64,{}", mime_type, base64))
+                } else {
+                    Value::Blob(bytes)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wumn_dao::data_uri_bytes;
+
+    const PNG: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+    #[test]
+    fn blob_policies() {
+        let uri = match BlobPolicy::default().to_value(PNG.to_vec()) {
+            Value::ImageUri(uri) => uri,
+            other => panic!("expecting a data uri, got {:?}", other),
+        };
+        assert!(uri.starts_with("data:image/png;base64,"));
+        assert_eq!(data_uri_bytes(&uri).unwrap(), PNG.to_vec());
+        assert_eq!(BlobPolicy::Raw.to_value(PNG.to_vec()), Value::Blob(PNG.to_vec()));
+        let jpeg_only = BlobPolicy::DataUri(vec!["image/jpeg".into()]);
+        assert_eq!(jpeg_only.to_value(PNG.to_vec()), Value::Blob(PNG.to_vec()));
+    }
+}
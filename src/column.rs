@@ -1,10 +1,12 @@
 use crate::{
+    common,
     types::SqlType,
     ColumnName,
-    FromDao,
     TableName,
+    Value,
 };
 use uuid::Uuid;
+use wumn_dao::ConvertError;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Column {
@@ -81,6 +83,36 @@ impl Column {
             }
         })
     }
+
+    /// cast the value into the type of this column before it is inserted, ie: a text from a form,
+    /// a text must fit in the limit of the column and a not null column must have a value
+    /// unless it has a default.
+    /// Only the arrays of int, float and text are cast, the others are an error
+    pub fn cast_value(&self, value: &Value) -> Result<Value, ConvertError> {
+        let value = common::cast_type(value, &self.specification.sql_type)?;
+        match value {
+            Value::Nil
+                if self.is_not_null()
+                    && !self.is_autoincrement()
+                    && !self.has_generated_default() =>
+            {
+                Err(ConvertError::NotSupported(
+                    "Nil".into(),
+                    format!("not null column {}", self.name.complete_name()),
+                ))
+            }
+            Value::Text(ref v) => match self.specification.get_limit() {
+                Some(limit) if v.chars().count() > limit as usize => {
+                    Err(ConvertError::NotSupported(
+                        format!("{:?}", value),
+                        format!("{}({})", self.name.complete_name(), limit),
+                    ))
+                }
+                _ => Ok(value),
+            },
+            _ => Ok(value),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -97,6 +129,24 @@ impl ColumnSpecification {
             None => None,
         }
     }
+
+    /// the type of the geometries of a postgis column, ie: `Point`
+    pub fn geometry_type(&self) -> Option<&str> {
+        match self.sql_type {
+            SqlType::Geometry(ref geometry_type, _) | SqlType::Geography(ref geometry_type, _) => {
+                geometry_type.as_deref()
+            }
+            _ => None,
+        }
+    }
+
+    /// the spatial reference identifier of a postgis column, ie: 4326
+    pub fn srid(&self) -> Option<i32> {
+        match self.sql_type {
+            SqlType::Geometry(_, srid) | SqlType::Geography(_, srid) => srid,
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -140,11 +190,15 @@ pub enum Literal {
 }
 
 /// column stat, derive from pg_stats
-#[derive(Debug, PartialEq, FromDao, Clone)]
+/// the values are decoded according to the sql type of the column
+#[derive(Debug, PartialEq, Clone)]
 pub struct ColumnStat {
     pub avg_width: i32, /* average width of the column, (the number of characters) */
-    //most_common_values: Value,//top 5 most common values
     pub n_distinct: f32, // the number of distinct values of these column
+    pub null_frac: f32, // fraction of the column entries that are null
+    pub most_common_values: Vec<(Value, f32)>, // most common values with their frequencies
+    pub histogram_bounds: Vec<Value>, // divides the values into groups of about equal population
+    pub correlation: Option<f32>, // correlation of the physical row order and the value order
 }
 
 impl From<i64> for Literal {
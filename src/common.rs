@@ -1,13 +1,19 @@
 use crate::{
     column::Capacity,
     types::SqlType,
+    Array,
+    IpNetwork,
+    MacAddr,
+    TimeTz,
     Value,
 };
 use bigdecimal::BigDecimal;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use log::*;
 use num_traits::ToPrimitive;
-use std::str::FromStr;
+use std::{convert::TryFrom, str::FromStr};
+use uuid::Uuid;
+use wumn_dao::ConvertError;
 
 pub fn extract_datatype_with_capacity(
     data_type: &str,
@@ -66,144 +72,421 @@ pub fn extract_datatype_with_capacity(
     }
 }
 
-pub fn cast_type(value: &Value, required_type: &SqlType) -> Value {
+/// cast the value into the required sql type, ie: the text typed in a form into an int.
+/// The numbers are narrowed only when they fit in the type and an empty text
+/// of a type other than text is null.
+pub fn cast_type(value: &Value, required_type: &SqlType) -> Result<Value, ConvertError> {
     if *value == Value::Nil || required_type.same_type(value) {
-        value.to_owned()
-    } else {
-        match *value {
-            Value::Smallint(v) => {
-                match *required_type {
-                    SqlType::Tinyint => Value::Tinyint(v as i8),
-                    SqlType::Int => Value::Int(i32::from(v)),
-                    SqlType::Bigint => Value::Bigint(i64::from(v)),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
-                }
-            }
-            Value::Int(v) => {
-                match *required_type {
-                    SqlType::Tinyint => Value::Tinyint(v as i8),
-                    SqlType::Smallint => Value::Smallint(v as i16),
-                    SqlType::Bigint => Value::Bigint(i64::from(v)),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
-                }
-            }
-            Value::Bigint(v) => {
-                match *required_type {
-                    SqlType::Tinyint => Value::Tinyint(v as i8),
-                    SqlType::Smallint => Value::Smallint(v as i16),
-                    SqlType::Int => Value::Int(v as i32),
-                    SqlType::Numeric => {
-                        let bigdecimal =
-                            BigDecimal::from_str(&format!("{}", v));
-                        assert!(bigdecimal.is_ok());
-                        Value::BigDecimal(bigdecimal.unwrap())
-                    }
-                    SqlType::Varchar => Value::Text(format!("{}", v)),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
+        return Ok(value.to_owned());
+    }
+    if let Value::Text(ref v) = *value {
+        if v.trim().is_empty() && !is_text_type(required_type) {
+            return Ok(Value::Nil);
+        }
+    }
+    let not_supported = || {
+        ConvertError::NotSupported(format!("{:?}", value), format!("{:?}", required_type))
+    };
+    match *required_type {
+        SqlType::Domain(_, ref base) => cast_type(value, base),
+        SqlType::Bool => to_bool(value).map(Value::Bool).ok_or_else(not_supported),
+        SqlType::Tinyint => to_i64(value)
+            .and_then(|v| i8::try_from(v).ok())
+            .map(Value::Tinyint)
+            .ok_or_else(not_supported),
+        SqlType::Smallint => to_i64(value)
+            .and_then(|v| i16::try_from(v).ok())
+            .map(Value::Smallint)
+            .ok_or_else(not_supported),
+        SqlType::Int => to_i64(value)
+            .and_then(|v| i32::try_from(v).ok())
+            .map(Value::Int)
+            .ok_or_else(not_supported),
+        SqlType::Bigint => to_i64(value).map(Value::Bigint).ok_or_else(not_supported),
+        SqlType::Real | SqlType::Float => to_f64(value)
+            .filter(|v| !v.is_finite() || v.abs() <= f64::from(f32::MAX))
+            .map(|v| Value::Float(v as f32))
+            .ok_or_else(not_supported),
+        SqlType::Double => to_f64(value).map(Value::Double).ok_or_else(not_supported),
+        SqlType::Numeric => BigDecimal::try_from(value).map(Value::BigDecimal),
+        SqlType::Char => char::try_from(value).map(Value::Char),
+        SqlType::Varchar
+        | SqlType::Tinytext
+        | SqlType::Mediumtext
+        | SqlType::Text
+        | SqlType::TsVector => to_text(value).map(Value::Text).ok_or_else(not_supported),
+        SqlType::Enum(_, ref choices) => to_text(value)
+            .filter(|v| choices.is_empty() || choices.contains(v))
+            .map(Value::Text)
+            .ok_or_else(not_supported),
+        SqlType::Json => serde_json::Value::try_from(value).map(Value::Json),
+        SqlType::Uuid => Uuid::try_from(value).map(Value::Uuid),
+        SqlType::Date => NaiveDate::try_from(value).map(Value::Date),
+        SqlType::Time => NaiveTime::try_from(value).map(Value::Time),
+        SqlType::TimeTz => TimeTz::try_from(value).map(Value::TimeTz),
+        SqlType::Timestamp => NaiveDateTime::try_from(value).map(Value::DateTime),
+        SqlType::TimestampTz => DateTime::<Utc>::try_from(value).map(Value::Timestamp),
+        SqlType::IpAddress => IpNetwork::try_from(value).map(Value::Inet),
+        SqlType::Cidr => IpNetwork::try_from(value).map(Value::Cidr),
+        SqlType::MacAddr => MacAddr::try_from(value).map(Value::MacAddr),
+        SqlType::Tinyblob
+        | SqlType::Mediumblob
+        | SqlType::Blob
+        | SqlType::Longblob
+        | SqlType::Varbinary => Vec::<u8>::try_from(value).map(Value::Blob),
+        SqlType::Array(ref element_type) if !is_array_element_type(element_type) => {
+            Err(ConvertError::NotSupported(
+                format!("{:?}", value),
+                format!("{:?}, only the arrays of int, float and text are cast", required_type),
+            ))
+        }
+        SqlType::Array(ref element_type) => {
+            let elements = array_elements(value).ok_or_else(not_supported)?;
+            cast_array(&elements, element_type).ok_or_else(not_supported)
+        }
+        _ => Err(not_supported()),
+    }
+}
+
+fn is_text_type(sql_type: &SqlType) -> bool {
+    match *sql_type {
+        SqlType::Char
+        | SqlType::Varchar
+        | SqlType::Tinytext
+        | SqlType::Mediumtext
+        | SqlType::Text
+        | SqlType::TsVector => true,
+        SqlType::Domain(_, ref base) => is_text_type(base),
+        _ => false,
+    }
+}
+
+/// the booleans of postgresql, ie: `t`, `yes`, `on`, `1`
+fn to_bool(value: &Value) -> Option<bool> {
+    match *value {
+        Value::Text(ref v) => match v.trim().to_lowercase().as_str() {
+            "t" | "true" | "y" | "yes" | "on" | "1" => Some(true),
+            "f" | "false" | "n" | "no" | "off" | "0" => Some(false),
+            _ => None,
+        },
+        Value::Char(_) => to_bool(&Value::Text(to_text(value)?)),
+        _ => match to_i64(value)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        },
+    }
+}
+
+/// the integer of a value without a fractional part
+fn to_i64(value: &Value) -> Option<i64> {
+    let integral = |v: f64| {
+        if v.fract() == 0.0 && v >= i64::MIN as f64 && v < i64::MAX as f64 {
+            Some(v as i64)
+        } else {
+            None
+        }
+    };
+    match *value {
+        Value::Tinyint(v) => Some(i64::from(v)),
+        Value::Smallint(v) => Some(i64::from(v)),
+        Value::Int(v) => Some(i64::from(v)),
+        Value::Bigint(v) => Some(v),
+        Value::Float(v) => integral(f64::from(v)),
+        Value::Double(v) => integral(v),
+        Value::BigDecimal(ref v) if v.with_scale(0) == *v => v.to_i64(),
+        Value::Text(ref v) => v
+            .trim()
+            .parse()
+            .ok()
+            .or_else(|| to_i64(&Value::BigDecimal(BigDecimal::from_str(v.trim()).ok()?))),
+        _ => None,
+    }
+}
+
+fn to_f64(value: &Value) -> Option<f64> {
+    match *value {
+        Value::Tinyint(v) => Some(f64::from(v)),
+        Value::Smallint(v) => Some(f64::from(v)),
+        Value::Int(v) => Some(f64::from(v)),
+        Value::Bigint(v) => Some(v as f64),
+        Value::Float(v) => Some(f64::from(v)),
+        Value::Double(v) => Some(v),
+        Value::BigDecimal(ref v) => v.to_f64(),
+        Value::Text(ref v) => v.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// the text of the scalar values, a timestamp is in rfc3339
+fn to_text(value: &Value) -> Option<String> {
+    let text = match *value {
+        Value::Bool(v) => v.to_string(),
+        Value::Tinyint(v) => v.to_string(),
+        Value::Smallint(v) => v.to_string(),
+        Value::Int(v) => v.to_string(),
+        Value::Bigint(v) => v.to_string(),
+        Value::Float(v) => v.to_string(),
+        Value::Double(v) => v.to_string(),
+        Value::BigDecimal(ref v) => v.to_string(),
+        Value::Char(v) => v.to_string(),
+        Value::Text(ref v) | Value::ImageUri(ref v) => v.to_owned(),
+        Value::Json(ref v) => v.to_string(),
+        Value::Uuid(ref v) => v.to_string(),
+        Value::Date(ref v) => v.to_string(),
+        Value::Time(ref v) => v.to_string(),
+        Value::TimeTz(ref v) => v.to_string(),
+        Value::DateTime(ref v) => v.to_string(),
+        Value::Timestamp(ref v) => v.to_rfc3339(),
+        Value::Inet(ref v) | Value::Cidr(ref v) => v.to_string(),
+        Value::MacAddr(ref v) => v.to_string(),
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// the elements of an array, of a json array or of the text of a json or a postgresql array,
+/// ie: `[1,2]` and `{1,2}`
+fn array_elements(value: &Value) -> Option<Vec<Value>> {
+    match *value {
+        Value::Array(Array::Int(ref v)) => Some(v.iter().map(|v| Value::Int(*v)).collect()),
+        Value::Array(Array::Float(ref v)) => Some(v.iter().map(|v| Value::Float(*v)).collect()),
+        Value::Array(Array::Text(ref v)) => Some(v.iter().map(|v| Value::Text(v.clone())).collect()),
+        Value::Json(serde_json::Value::Array(ref elements)) => elements
+            .iter()
+            .map(|element| match *element {
+                serde_json::Value::Number(ref v) => v
+                    .as_i64()
+                    .map(Value::Bigint)
+                    .or_else(|| v.as_f64().map(Value::Double)),
+                serde_json::Value::String(ref v) => Some(Value::Text(v.clone())),
+                _ => None,
+            })
+            .collect(),
+        Value::Text(ref v) if v.trim_start().starts_with('[') => {
+            array_elements(&Value::Json(serde_json::from_str(v).ok()?))
+        }
+        Value::Text(ref v) => parse_array_literal(v)
+            .into_iter()
+            .map(|element| element.map(Value::Text))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// the element types of the arrays which are cast
+fn is_array_element_type(sql_type: &SqlType) -> bool {
+    matches!(
+        *sql_type,
+        SqlType::Int | SqlType::Real | SqlType::Float | SqlType::Varchar | SqlType::Text
+    )
+}
+
+/// the arrays hold ints, floats or texts and can not have null elements
+fn cast_array(elements: &[Value], element_type: &SqlType) -> Option<Value> {
+    let cast = |element: &Value| cast_type(element, element_type).ok();
+    let array = match *element_type {
+        SqlType::Int => Array::Int(
+            elements
+                .iter()
+                .map(|element| match cast(element)? {
+                    Value::Int(v) => Some(v),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+        ),
+        SqlType::Real | SqlType::Float => Array::Float(
+            elements
+                .iter()
+                .map(|element| match cast(element)? {
+                    Value::Float(v) => Some(v),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+        ),
+        SqlType::Varchar | SqlType::Text => Array::Text(
+            elements
+                .iter()
+                .map(|element| match cast(element)? {
+                    Value::Text(v) => Some(v),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+        ),
+        _ => return None,
+    };
+    Some(Value::Array(array))
+}
+
+/// split the text representation of an array such as `{1,"a b",NULL}` into its elements,
+/// unquoted NULL elements are None
+pub(crate) fn parse_array_literal(array: &str) -> Vec<Option<String>> {
+    let array = array.trim();
+    let inner = array
+        .strip_prefix('{')
+        .and_then(|array| array.strip_suffix('}'))
+        .unwrap_or(array);
+    let mut elements = vec![];
+    if inner.is_empty() {
+        return elements;
+    }
+    let mut chars = inner.chars();
+    let mut element = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut depth = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_quotes => {
+                if let Some(escaped) = chars.next() {
+                    element.push(escaped);
                 }
             }
-            Value::BigDecimal(ref v) => {
-                match *required_type {
-                    SqlType::Int => {
-                        let ival = v.to_i32();
-                        assert!(ival.is_some());
-                        let ival = ival.unwrap();
-                        Value::Int(ival)
-                    }
-                    SqlType::Varchar => Value::Text(format!("{}", v)),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
-                }
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
             }
-            Value::Text(ref v) => {
-                match *required_type {
-                    SqlType::Timestamp => {
-                        let ts = NaiveDateTime::parse_from_str(
-                            &v,
-                            "%Y-%m-%d %H:%M:%S",
-                        );
-                        let ts = if let Ok(ts) = ts {
-                            ts
-                        } else {
-                            let ts = NaiveDateTime::parse_from_str(
-                                &v,
-                                "%Y-%m-%d %H:%M:%S.%f",
-                            );
-                            if let Ok(ts) = ts {
-                                ts
-                            } else {
-                                panic!("unable to parse timestamp: {}", v);
-                            }
-                        };
-                        Value::DateTime(ts)
-                    }
-                    SqlType::Char => {
-                        assert_eq!(v.len(), 1);
-                        Value::Char(v.chars().next().unwrap())
-                    }
-                    SqlType::Int => {
-                        if let Ok(v) = v.parse::<i32>() {
-                            Value::Int(v)
-                        } else {
-                            panic!(
-                                "unsupported conversion from {:?} to {:?}",
-                                value, required_type
-                            );
-                        }
-                    }
-                    // enums will be just Text
-                    SqlType::Enum(_,_) => Value::Text(v.to_string()),
-                    // ts vector is casted into text and then we just
-                    // return them as text as well
-                    SqlType::TsVector => Value::Text(v.to_string()),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
-                }
+            '{' if !in_quotes => {
+                depth += 1;
+                element.push(c);
             }
-            Value::ImageUri(_) => {
-                info!("passing ImageUri as is");
-                value.clone()
+            '}' if !in_quotes => {
+                depth -= 1;
+                element.push(c);
             }
-            Value::Char(v) => {
-                match *required_type {
-                    SqlType::Varchar => Value::Text(format!("{}", v)),
-                    _ => {
-                        panic!(
-                            "unsupported conversion from {:?} to {:?}",
-                            value, required_type
-                        )
-                    }
-                }
+            ',' if !in_quotes && depth == 0 => {
+                elements.push(to_array_element(&element, quoted));
+                element.clear();
+                quoted = false;
             }
-            _ => {
-                panic!(
-                    "unsupported conversion from {:?} to {:?}",
-                    value, required_type
-                )
+            _ => element.push(c),
+        }
+    }
+    elements.push(to_array_element(&element, quoted));
+    elements
+}
+
+fn to_array_element(element: &str, quoted: bool) -> Option<String> {
+    if !quoted && element == "NULL" {
+        None
+    } else {
+        Some(element.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        column::{ColumnConstraint, ColumnSpecification},
+        Column,
+        ColumnName,
+        TableName,
+    };
+
+    fn text(v: &str) -> Value {
+        Value::Text(v.to_string())
+    }
+
+    #[test]
+    fn cast_numbers() {
+        assert_eq!(cast_type(&Value::Int(42), &SqlType::Tinyint).unwrap(), Value::Tinyint(42));
+        assert!(cast_type(&Value::Int(300), &SqlType::Tinyint).is_err());
+        assert!(cast_type(&Value::Bigint(i64::MAX), &SqlType::Int).is_err());
+        assert_eq!(cast_type(&text(" 42 "), &SqlType::Smallint).unwrap(), Value::Smallint(42));
+        assert_eq!(cast_type(&text("42.0"), &SqlType::Int).unwrap(), Value::Int(42));
+        assert!(cast_type(&text("42.5"), &SqlType::Int).is_err());
+        assert!(cast_type(&Value::Double(1.5), &SqlType::Bigint).is_err());
+        assert!(cast_type(&Value::Double(1e300), &SqlType::Float).is_err());
+        assert_eq!(cast_type(&text("1.5"), &SqlType::Double).unwrap(), Value::Double(1.5));
+        assert_eq!(
+            cast_type(&text("1.50"), &SqlType::Numeric).unwrap(),
+            Value::BigDecimal(BigDecimal::from_str("1.50").unwrap())
+        );
+        assert_eq!(cast_type(&text("yes"), &SqlType::Bool).unwrap(), Value::Bool(true));
+        assert!(cast_type(&text("maybe"), &SqlType::Bool).is_err());
+        assert_eq!(cast_type(&text(""), &SqlType::Int).unwrap(), Value::Nil);
+        assert_eq!(cast_type(&text(""), &SqlType::Text).unwrap(), text(""));
+        assert_eq!(cast_type(&Value::Int(7), &SqlType::Varchar).unwrap(), text("7"));
+    }
+
+    #[test]
+    fn cast_texts() {
+        let uuid = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+        assert_eq!(
+            cast_type(&text(uuid), &SqlType::Uuid).unwrap(),
+            Value::Uuid(Uuid::parse_str(uuid).unwrap())
+        );
+        assert!(cast_type(&text("not a uuid"), &SqlType::Uuid).is_err());
+        assert_eq!(
+            cast_type(&text("2020-02-29"), &SqlType::Date).unwrap(),
+            Value::Date(NaiveDate::from_ymd_opt(2020, 2, 29).unwrap())
+        );
+        assert!(cast_type(&text("2021-02-29"), &SqlType::Date).is_err());
+        assert_eq!(
+            cast_type(&text("2020-02-29 10:30:00"), &SqlType::Timestamp).unwrap(),
+            Value::DateTime(NaiveDateTime::parse_from_str("2020-02-29 10:30:00", "%Y-%m-%d %H:%M:%S").unwrap())
+        );
+        assert_eq!(
+            cast_type(&text(r#"{"a":1}"#), &SqlType::Json).unwrap(),
+            Value::Json(serde_json::json!({"a": 1}))
+        );
+        let mood = SqlType::Enum("mood".into(), vec!["happy".into(), "sad".into()]);
+        assert_eq!(cast_type(&text("sad"), &mood).unwrap(), text("sad"));
+        assert!(cast_type(&text("angry"), &mood).is_err());
+        let positive = SqlType::Domain("positive".into(), Box::new(SqlType::Int));
+        assert_eq!(cast_type(&text("1"), &positive).unwrap(), Value::Int(1));
+    }
+
+    #[test]
+    fn cast_arrays() {
+        let ints = SqlType::Array(Box::new(SqlType::Int));
+        assert_eq!(
+            cast_type(&text("{1,2,3}"), &ints).unwrap(),
+            Value::Array(Array::Int(vec![1, 2, 3]))
+        );
+        assert_eq!(
+            cast_type(&text("[1, 2]"), &ints).unwrap(),
+            Value::Array(Array::Int(vec![1, 2]))
+        );
+        assert!(cast_type(&text("{1,NULL}"), &ints).is_err());
+        assert!(cast_type(&text("{1,a}"), &ints).is_err());
+        assert_eq!(
+            cast_type(&Value::Array(Array::Int(vec![1])), &SqlType::Array(Box::new(SqlType::Text)))
+                .unwrap(),
+            Value::Array(Array::Text(vec!["1".into()]))
+        );
+        let dates = SqlType::Array(Box::new(SqlType::Date));
+        match cast_type(&text("{2020-01-01}"), &dates) {
+            Err(ConvertError::NotSupported(_, message)) => {
+                assert!(message.contains("only the arrays of int, float and text"), "{}", message)
             }
+            other => panic!("expecting the unsupported array, got {:?}", other),
         }
     }
+
+    #[test]
+    fn cast_column_values() {
+        let column = Column {
+            table: TableName::from("public.actor"),
+            name: ColumnName::from("first_name"),
+            comment: None,
+            specification: ColumnSpecification {
+                sql_type: SqlType::Varchar,
+                capacity: Some(Capacity::Limit(5)),
+                constraints: vec![ColumnConstraint::NotNull],
+            },
+            stat: None,
+        };
+        assert_eq!(column.cast_value(&text("Ñandú")).unwrap(), text("Ñandú"));
+        assert!(column.cast_value(&text("Penelope")).is_err());
+        assert!(column.cast_value(&Value::Nil).is_err());
+        let mut id = column.clone();
+        id.specification = ColumnSpecification {
+            sql_type: SqlType::Int,
+            capacity: None,
+            constraints: vec![ColumnConstraint::NotNull, ColumnConstraint::AutoIncrement],
+        };
+        assert_eq!(id.cast_value(&text("")).unwrap(), Value::Nil);
+    }
 }
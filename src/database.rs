@@ -1,9 +1,14 @@
 use crate::{
+    blob::BlobPolicy,
+    notification::Notification,
+    observer::QueryObserver,
     table::SchemaContent,
     users::{
         Role,
         User,
+        UserPrivilege,
     },
+    ColumnName,
     EntityManager,
     Rows,
     Table,
@@ -11,6 +16,7 @@ use crate::{
     Value,
 };
 use serde::Serialize;
+use std::{io::Write, time::Duration};
 
 use crate::DbError;
 use wumn_codegen::FromDao;
@@ -29,6 +35,16 @@ pub trait Database {
         param: &[&Value],
     ) -> Result<Rows, DbError>;
 
+    /// notify this observer of every sql executed after this
+    fn add_observer(&self, observer: Box<dyn QueryObserver>);
+
+    /// how the blobs of the sql executed after this are returned, returns the previous policy
+    fn set_blob_policy(&self, policy: BlobPolicy) -> BlobPolicy;
+
+    /// run the body in a transaction, or in a savepoint when a transaction is already open,
+    /// the changes of the body are rolled back when it fails
+    fn atomically(&self, body: &mut dyn FnMut() -> Result<(), DbError>) -> Result<(), DbError>;
+
     fn get_table(
         &self,
         em: &EntityManager,
@@ -51,8 +67,92 @@ pub trait Database {
         username: &str,
     ) -> Result<Vec<Role>, DbError>;
 
+    fn get_privileges(
+        &self,
+        em: &EntityManager,
+        username: &str,
+    ) -> Result<Vec<UserPrivilege>, DbError>;
+
+    fn create_role(
+        &self,
+        em: &EntityManager,
+        user: &User,
+        password: Option<&str>,
+    ) -> Result<(), DbError>;
+
+    fn alter_role(&self, em: &EntityManager, user: &User) -> Result<(), DbError>;
+
+    fn drop_role(&self, em: &EntityManager, username: &str) -> Result<(), DbError>;
+
+    fn set_password(
+        &self,
+        em: &EntityManager,
+        username: &str,
+        password: &str,
+    ) -> Result<(), DbError>;
+
+    fn grant_role(
+        &self,
+        em: &EntityManager,
+        role: &Role,
+        username: &str,
+    ) -> Result<(), DbError>;
+
+    fn revoke_role(
+        &self,
+        em: &EntityManager,
+        role: &Role,
+        username: &str,
+    ) -> Result<(), DbError>;
+
+    fn grant_privilege(
+        &self,
+        em: &EntityManager,
+        privilege: &UserPrivilege,
+    ) -> Result<(), DbError>;
+
+    fn revoke_privilege(
+        &self,
+        em: &EntityManager,
+        privilege: &UserPrivilege,
+    ) -> Result<(), DbError>;
+
     fn get_database_name(
         &self,
         em: &EntityManager,
     ) -> Result<Option<DatabaseName>, DbError>;
+
+    /// bulk load the rows into the columns of the table, returns the number of rows copied
+    fn copy_in(
+        &self,
+        table_name: &TableName,
+        column_names: &[ColumnName],
+        rows: &mut dyn Iterator<Item = Vec<Value>>,
+    ) -> Result<u64, DbError>;
+
+    fn copy_out(
+        &self,
+        table_name: &TableName,
+        column_names: &[ColumnName],
+    ) -> Result<Rows, DbError>;
+
+    /// export the columns of the table as csv, returns the number of bytes written
+    fn copy_out_csv(
+        &self,
+        table_name: &TableName,
+        column_names: &[ColumnName],
+        writer: &mut dyn Write,
+    ) -> Result<u64, DbError>;
+
+    fn listen(&self, channel: &str) -> Result<(), DbError>;
+
+    fn unlisten(&self, channel: &str) -> Result<(), DbError>;
+
+    fn notify(&self, channel: &str, payload: &str) -> Result<(), DbError>;
+
+    /// wait for the next notification, blocking indefinitely when there is no timeout
+    fn poll_notification(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Notification>, DbError>;
 }
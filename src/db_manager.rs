@@ -1,21 +1,52 @@
+use crate::blob::BlobPolicy;
 use crate::dao_manager::DaoManager;
 use crate::entity::EntityManager;
 use crate::error::{ConnectError, DbError};
 use crate::platform::{DBPlatform, Platform};
 use cfg_if::cfg_if;
 use log::*;
-use std::cell::RefCell;
 use std::convert::TryFrom;
 
 cfg_if! {if #[cfg(feature = "with-postgres")]{
+    use crate::database::Database;
     use crate::pg::{self, PostgresDB};
 }}
 
-pub struct DbManager;
+pub struct DbManager {
+    /// the number of prepared statements cached per connection,
+    /// the platform default is used when not set
+    statement_cache_size: Option<usize>,
+    /// the timezone of the sessions, the server default is used when not set
+    timezone: Option<String>,
+    /// how the blobs are returned by the connections
+    blob_policy: BlobPolicy,
+}
 
 impl DbManager {
     pub fn new() -> Self {
-        DbManager
+        DbManager {
+            statement_cache_size: None,
+            timezone: None,
+            blob_policy: BlobPolicy::default(),
+        }
+    }
+
+    /// cache up to this number of prepared statements in the connections created after this,
+    /// 0 disables the caching
+    pub fn set_statement_cache_size(&mut self, size: usize) {
+        self.statement_cache_size = Some(size);
+    }
+
+    /// set the timezone of the connections created after this, ie: `Asia/Manila`,
+    /// which the server uses to convert the timestamps to dates or text.
+    /// It is not used for the timestamp columns without timezone, they get the utc time.
+    pub fn set_timezone(&mut self, timezone: &str) {
+        self.timezone = Some(timezone.to_string());
+    }
+
+    /// how the blobs are returned by the connections created after this
+    pub fn set_blob_policy(&mut self, policy: BlobPolicy) {
+        self.blob_policy = policy;
     }
 
     /// ensure that a connection pool for this db_url exist
@@ -27,9 +58,15 @@ impl DbManager {
                 #[cfg(feature = "with-postgres")]
                 Platform::Postgres => {
                     let conn = pg::init_connection(db_url);
-                    Ok(DBPlatform::Postgres(Box::new(PostgresDB(RefCell::new(
-                        conn,
-                    )))))
+                    let cache_size = self
+                        .statement_cache_size
+                        .unwrap_or(pg::DEFAULT_STATEMENT_CACHE_SIZE);
+                    let pg = PostgresDB::with_statement_cache_size(conn, cache_size);
+                    if let Some(ref timezone) = self.timezone {
+                        pg.set_timezone(timezone)?;
+                    }
+                    pg.set_blob_policy(self.blob_policy.clone());
+                    Ok(DBPlatform::Postgres(Box::new(pg)))
                 }
                 Platform::Unsupported(scheme) => {
                     info!("unsupported");
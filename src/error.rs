@@ -102,10 +102,40 @@ impl From<PlatformError> for DbError {
 pub enum ConvertError {
     UnknownDataType,
     UnsupportedDataType(String),
+    JsonError(serde_json::Error),
+}
+
+/// the conversion errors of the values of an entity
+impl From<wumn_dao::ConvertError> for DbError {
+    fn from(e: wumn_dao::ConvertError) -> Self {
+        let e = match e {
+            wumn_dao::ConvertError::JsonError(e) => ConvertError::JsonError(e),
+            wumn_dao::ConvertError::NotSupported(value, ty) => {
+                ConvertError::UnsupportedDataType(format!("{} to {}", value, ty))
+            }
+            wumn_dao::ConvertError::SerdeError(msg) => ConvertError::UnsupportedDataType(msg),
+        };
+        DbError::ConvertError(e)
+    }
 }
 
 #[derive(Debug)]
 pub enum DataError {
     ZeroRecordReturned,
     MoreThan1RecordReturned,
+    /// there is no enum type of this name in the database
+    EnumNotFound(String),
+    /// the enum type of this name in the database has other choices,
+    /// the choices missing in the rust enum and the ones not in the database
+    EnumMismatch {
+        enum_name: String,
+        missing: Vec<String>,
+        unknown: Vec<String>,
+    },
+    /// the row at this index has a different number of values than the columns
+    RowLengthMismatch {
+        row: usize,
+        columns: usize,
+        values: usize,
+    },
 }
@@ -91,6 +91,7 @@ extern crate postgres_types;
 mod pg;
 }}
 
+mod blob;
 pub mod column;
 pub mod common;
 mod dao_manager;
@@ -98,6 +99,9 @@ mod database;
 mod db_manager;
 mod entity;
 pub mod error;
+pub mod mock;
+mod notification;
+pub mod observer;
 mod platform;
 // mod pool;
 pub mod table;
@@ -105,27 +109,34 @@ pub mod types;
 mod users;
 pub mod util;
 
+pub use blob::BlobPolicy;
 pub use column::Column;
 pub use dao_manager::DaoManager;
 pub use database::{Database, DatabaseName};
 pub use db_manager::DbManager;
 pub use entity::EntityManager;
 pub use error::{DataError, DbError, PlatformError};
+pub use notification::{Listener, Notification};
+pub use observer::{QueryEvent, QueryObserver, SlowQueryLogger};
 pub use platform::DBPlatform;
 pub use table::Table;
+pub use users::{Privilege, Role, User, UserPrivilege};
 
 // we export the traits that has a derived proc macro
 // this are used in the apps
-pub use codegen::{FromDao, ToColumnNames, ToDao, ToTableName};
+pub use codegen::{DbComposite, DbEnum, FromDao, ToColumnNames, ToDao, ToTableName};
 
-pub use wumn_dao::{Array, ColumnName, Dao, Rows, TableName, ToValue, Value};
+pub use wumn_dao::{
+    from_dao, to_dao, Array, Circle, ColumnName, CsvWriter, Dao, IpNetwork, Json, LineEquation,
+    MacAddr, NdjsonWriter, Range, Row, Rows, Spatial, TableName, TimeTz, ToValue, Value,
+};
 
 /// Wrap the wumn_dao exports to avoid name conflict with the wumn_codegen
 pub mod dao {
-    pub use wumn_dao::{FromDao, ToColumnNames, ToDao, ToTableName};
+    pub use wumn_dao::{DbEnum, DbValue, FromDao, ToColumnNames, ToDao, ToTableName};
 }
 
 /// Wrap the wumn_codegen exports to avoid name conflict with the wumn_dao
 pub mod codegen {
-    pub use wumn_codegen::{FromDao, ToColumnNames, ToDao, ToTableName};
+    pub use wumn_codegen::{DbComposite, DbEnum, FromDao, ToColumnNames, ToDao, ToTableName};
 }
@@ -0,0 +1,420 @@
+//! An in-memory database which returns canned results for the sql it is given,
+//! used for testing the code using the entity manager without a database server.
+//!
+//! ```rust
+//! use wumn::{mock::{MockDB, SqlMatcher}, Rows, Value};
+//!
+//! let mock = MockDB::new();
+//! let mut rows = Rows::new(vec!["actor_id".into()]);
+//! rows.push(vec![Value::Int(1)]);
+//! mock.expect(SqlMatcher::contains("FROM actor"), rows);
+//! let em = mock.em();
+//! let rows = em.db().execute_sql_with_return("SELECT actor_id FROM actor", &[]).unwrap();
+//! assert_eq!(rows.data.len(), 1);
+//! assert_eq!(mock.executed()[0].sql, "SELECT actor_id FROM actor");
+//! ```
+use crate::{
+    blob::BlobPolicy,
+    notification::Notification,
+    observer::{QueryEvent, QueryObserver},
+    table::SchemaContent,
+    users::{Role, User, UserPrivilege},
+    ColumnName, DBPlatform, DaoManager, Database, DatabaseName, DbError, EntityManager, Rows,
+    Table, TableName, Value,
+};
+use std::{
+    io::Write,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// How the executed sql is matched to the expected ones
+pub enum SqlMatcher {
+    /// the same sql, ignoring the differences in whitespaces
+    Exact(String),
+    /// the sql contains this text
+    Contains(String),
+    Predicate(Box<dyn Fn(&str) -> bool + Send>),
+}
+
+impl SqlMatcher {
+    pub fn exact(sql: &str) -> Self {
+        SqlMatcher::Exact(sql.to_string())
+    }
+
+    pub fn contains(pattern: &str) -> Self {
+        SqlMatcher::Contains(pattern.to_string())
+    }
+
+    pub fn predicate<F>(predicate: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + 'static,
+    {
+        SqlMatcher::Predicate(Box::new(predicate))
+    }
+
+    pub fn is_match(&self, sql: &str) -> bool {
+        match *self {
+            SqlMatcher::Exact(ref expected) => normalize(expected) == normalize(sql),
+            SqlMatcher::Contains(ref pattern) => sql.contains(pattern.as_str()),
+            SqlMatcher::Predicate(ref predicate) => predicate(sql),
+        }
+    }
+}
+
+fn normalize(sql: &str) -> String {
+    sql.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+enum MockResult {
+    Rows(Rows),
+    /// DbError can not be cloned so it is created each time the sql is executed
+    Error(Box<dyn Fn() -> DbError + Send>),
+}
+
+struct Expectation {
+    matcher: SqlMatcher,
+    result: MockResult,
+}
+
+/// An sql executed against the mock database
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExecutedSql {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+#[derive(Default)]
+struct MockState {
+    expectations: Vec<Expectation>,
+    executed: Vec<ExecutedSql>,
+    observers: Vec<Box<dyn QueryObserver>>,
+    /// kept for the code that sets it, the canned rows are returned as they are
+    blob_policy: BlobPolicy,
+}
+
+/// The mock database, clones share the same expectations and executed sql
+/// so the test can keep one to inspect what the entity manager executed,
+/// even from another thread.
+///
+/// The expectations are tried in the order they are added, the first match is used.
+/// Executing an sql which matches none of them is an error.
+#[derive(Default, Clone)]
+pub struct MockDB(Arc<Mutex<MockState>>);
+
+impl MockDB {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// the state is still usable after a test panicked while holding it
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// return these rows for the sql that matches
+    pub fn expect(&self, matcher: SqlMatcher, rows: Rows) {
+        self.state().expectations.push(Expectation {
+            matcher,
+            result: MockResult::Rows(rows),
+        });
+    }
+
+    /// fail with the error created by this function for the sql that matches
+    pub fn expect_error<F>(&self, matcher: SqlMatcher, error: F)
+    where
+        F: Fn() -> DbError + Send + 'static,
+    {
+        self.state().expectations.push(Expectation {
+            matcher,
+            result: MockResult::Error(Box::new(error)),
+        });
+    }
+
+    /// the sql executed so far and their parameters, in the order they were executed
+    pub fn executed(&self) -> Vec<ExecutedSql> {
+        self.state().executed.clone()
+    }
+
+    pub fn clear_executed(&self) {
+        self.state().executed.clear();
+    }
+
+    /// an entity manager using this mock database
+    pub fn em(&self) -> EntityManager {
+        EntityManager(DBPlatform::Mock(Box::new(self.clone())))
+    }
+
+    /// a dao manager using this mock database
+    pub fn dm(&self) -> DaoManager {
+        DaoManager(DBPlatform::Mock(Box::new(self.clone())))
+    }
+
+    fn record(&self, sql: &str, params: &[&Value]) {
+        self.state().executed.push(ExecutedSql {
+            sql: sql.to_string(),
+            params: params.iter().map(|v| (*v).clone()).collect(),
+        });
+    }
+
+    fn result_of(&self, sql: &str) -> Result<Rows, DbError> {
+        let state = self.state();
+        let expectation = state.expectations.iter().find(|e| e.matcher.is_match(sql));
+        match expectation {
+            Some(expectation) => match expectation.result {
+                MockResult::Rows(ref rows) => Ok(rows.clone()),
+                MockResult::Error(ref error) => Err(error()),
+            },
+            None => Err(DbError::UnsupportedOperation(format!(
+                "no mocked result for sql: {}",
+                sql
+            ))),
+        }
+    }
+
+    fn unsupported<T>(operation: &str) -> Result<T, DbError> {
+        Err(DbError::UnsupportedOperation(format!(
+            "{} is not supported by the mock database",
+            operation
+        )))
+    }
+}
+
+impl Database for MockDB {
+    fn execute_sql_with_return(&self, sql: &str, param: &[&Value]) -> Result<Rows, DbError> {
+        let start = Instant::now();
+        self.record(sql, param);
+        let rows = self.result_of(sql);
+        let duration = start.elapsed();
+        // the observers are taken out while they are notified, so they can use the mock too
+        let observers = std::mem::take(&mut self.state().observers);
+        if !observers.is_empty() {
+            let event =
+                QueryEvent::new(sql, param, duration, rows.as_ref().map(|rows| rows.data.len()));
+            for observer in observers.iter() {
+                observer.on_query(&event);
+            }
+        }
+        let mut state = self.state();
+        let added = std::mem::replace(&mut state.observers, observers);
+        state.observers.extend(added);
+        rows
+    }
+
+    fn add_observer(&self, observer: Box<dyn QueryObserver>) {
+        self.state().observers.push(observer);
+    }
+
+    fn set_blob_policy(&self, policy: BlobPolicy) -> BlobPolicy {
+        std::mem::replace(&mut self.state().blob_policy, policy)
+    }
+
+    /// there are no transactions, the body is just run
+    fn atomically(&self, body: &mut dyn FnMut() -> Result<(), DbError>) -> Result<(), DbError> {
+        body()
+    }
+
+    fn get_table(&self, _em: &EntityManager, _table_name: &TableName) -> Result<Table, DbError> {
+        Self::unsupported("get_table")
+    }
+
+    fn get_all_tables(&self, _em: &EntityManager) -> Result<Vec<Table>, DbError> {
+        Self::unsupported("get_all_tables")
+    }
+
+    fn get_grouped_tables(&self, _em: &EntityManager) -> Result<Vec<SchemaContent>, DbError> {
+        Self::unsupported("get_grouped_tables")
+    }
+
+    fn get_users(&self, _em: &EntityManager) -> Result<Vec<User>, DbError> {
+        Self::unsupported("get_users")
+    }
+
+    fn get_roles(&self, _em: &EntityManager, _username: &str) -> Result<Vec<Role>, DbError> {
+        Self::unsupported("get_roles")
+    }
+
+    fn get_privileges(
+        &self,
+        _em: &EntityManager,
+        _username: &str,
+    ) -> Result<Vec<UserPrivilege>, DbError> {
+        Self::unsupported("get_privileges")
+    }
+
+    fn create_role(
+        &self,
+        _em: &EntityManager,
+        _user: &User,
+        _password: Option<&str>,
+    ) -> Result<(), DbError> {
+        Self::unsupported("create_role")
+    }
+
+    fn alter_role(&self, _em: &EntityManager, _user: &User) -> Result<(), DbError> {
+        Self::unsupported("alter_role")
+    }
+
+    fn drop_role(&self, _em: &EntityManager, _username: &str) -> Result<(), DbError> {
+        Self::unsupported("drop_role")
+    }
+
+    fn set_password(
+        &self,
+        _em: &EntityManager,
+        _username: &str,
+        _password: &str,
+    ) -> Result<(), DbError> {
+        Self::unsupported("set_password")
+    }
+
+    fn grant_role(&self, _em: &EntityManager, _role: &Role, _username: &str) -> Result<(), DbError> {
+        Self::unsupported("grant_role")
+    }
+
+    fn revoke_role(
+        &self,
+        _em: &EntityManager,
+        _role: &Role,
+        _username: &str,
+    ) -> Result<(), DbError> {
+        Self::unsupported("revoke_role")
+    }
+
+    fn grant_privilege(
+        &self,
+        _em: &EntityManager,
+        _privilege: &UserPrivilege,
+    ) -> Result<(), DbError> {
+        Self::unsupported("grant_privilege")
+    }
+
+    fn revoke_privilege(
+        &self,
+        _em: &EntityManager,
+        _privilege: &UserPrivilege,
+    ) -> Result<(), DbError> {
+        Self::unsupported("revoke_privilege")
+    }
+
+    fn get_database_name(&self, _em: &EntityManager) -> Result<Option<DatabaseName>, DbError> {
+        Self::unsupported("get_database_name")
+    }
+
+    fn copy_in(
+        &self,
+        _table_name: &TableName,
+        _column_names: &[ColumnName],
+        _rows: &mut dyn Iterator<Item = Vec<Value>>,
+    ) -> Result<u64, DbError> {
+        Self::unsupported("copy_in")
+    }
+
+    fn copy_out(
+        &self,
+        _table_name: &TableName,
+        _column_names: &[ColumnName],
+    ) -> Result<Rows, DbError> {
+        Self::unsupported("copy_out")
+    }
+
+    fn copy_out_csv(
+        &self,
+        _table_name: &TableName,
+        _column_names: &[ColumnName],
+        _writer: &mut dyn Write,
+    ) -> Result<u64, DbError> {
+        Self::unsupported("copy_out_csv")
+    }
+
+    fn listen(&self, channel: &str) -> Result<(), DbError> {
+        self.record(&format!("LISTEN {}", channel), &[]);
+        Ok(())
+    }
+
+    fn unlisten(&self, channel: &str) -> Result<(), DbError> {
+        self.record(&format!("UNLISTEN {}", channel), &[]);
+        Ok(())
+    }
+
+    fn notify(&self, channel: &str, payload: &str) -> Result<(), DbError> {
+        self.record(
+            "SELECT pg_notify($1, $2)",
+            &[&Value::Text(channel.to_string()), &Value::Text(payload.to_string())],
+        );
+        Ok(())
+    }
+
+    /// there is never a notification to receive
+    fn poll_notification(
+        &self,
+        _timeout: Option<Duration>,
+    ) -> Result<Option<Notification>, DbError> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{DataError, FromDao, ToColumnNames, ToTableName};
+
+    #[derive(Debug, PartialEq, FromDao, ToColumnNames, ToTableName)]
+    struct Actor {
+        actor_id: i32,
+        first_name: String,
+    }
+
+    #[test]
+    fn canned_rows() {
+        let mock = MockDB::new();
+        let mut rows = Rows::new(vec!["actor_id".into(), "first_name".into()]);
+        rows.push(vec![Value::Int(1), Value::Text("PENELOPE".into())]);
+        mock.expect(SqlMatcher::contains("FROM actor"), rows);
+        let em = mock.em();
+        let actors: Vec<Actor> = em.get_all().unwrap();
+        assert_eq!(
+            actors,
+            vec![Actor {
+                actor_id: 1,
+                first_name: "PENELOPE".into()
+            }]
+        );
+        assert_eq!(mock.executed().len(), 1);
+    }
+
+    #[test]
+    fn record_params_and_match_exact() {
+        let mock = MockDB::new();
+        mock.expect(
+            SqlMatcher::exact("SELECT actor_id, first_name FROM actor WHERE actor_id = $1"),
+            Rows::new(vec![]),
+        );
+        let dm = mock.dm();
+        let rows = dm.execute_sql_with_return(
+            "SELECT actor_id, first_name\n  FROM actor WHERE actor_id = $1",
+            &[&Value::Int(1)],
+        );
+        assert!(rows.is_ok());
+        assert_eq!(mock.executed()[0].params, vec![Value::Int(1)]);
+        assert!(dm.execute_sql_with_return("SELECT 1", &[]).is_err());
+    }
+
+    #[test]
+    fn configured_error() {
+        let mock = MockDB::new();
+        mock.expect_error(SqlMatcher::predicate(|sql| sql.starts_with("DELETE")), || {
+            DbError::DataError(DataError::ZeroRecordReturned)
+        });
+        let em = mock.em();
+        let result = em.db().execute_sql_with_return("DELETE FROM actor", &[]);
+        match result {
+            Err(DbError::DataError(DataError::ZeroRecordReturned)) => (),
+            _ => panic!("expecting the configured error"),
+        }
+        match em.get_database_name() {
+            Err(DbError::UnsupportedOperation(_)) => (),
+            _ => panic!("expecting the introspection to be unsupported"),
+        }
+    }
+}
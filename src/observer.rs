@@ -0,0 +1,171 @@
+//! Observers are notified of every sql executed through `Database::execute_sql_with_return`,
+//! with how long it took and how it ended, to find the queries that are slow or failing.
+use crate::{DbError, Value};
+use log::*;
+use std::time::Duration;
+
+/// An sql that was executed
+pub struct QueryEvent<'a> {
+    pub sql: &'a str,
+    params: &'a [&'a Value],
+    pub duration: Duration,
+    /// the number of rows returned, None when the sql failed
+    pub rows: Option<usize>,
+    pub error: Option<&'a DbError>,
+}
+
+impl<'a> QueryEvent<'a> {
+    pub(crate) fn new(
+        sql: &'a str,
+        params: &'a [&'a Value],
+        duration: Duration,
+        result: Result<usize, &'a DbError>,
+    ) -> Self {
+        let (rows, error) = match result {
+            Ok(rows) => (Some(rows), None),
+            Err(e) => (None, Some(e)),
+        };
+        QueryEvent {
+            sql,
+            params,
+            duration,
+            rows,
+            error,
+        }
+    }
+
+    pub fn param_count(&self) -> usize {
+        self.params.len()
+    }
+
+    /// the parameters with their content, which may be sensitive
+    pub fn params(&self) -> &[&Value] {
+        self.params
+    }
+
+    /// the type of each of the parameters without its content, safe to be logged
+    pub fn redacted_params(&self) -> Vec<String> {
+        self.params.iter().map(|v| redact(v).to_string()).collect()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// the variant name of the value, ie: `Text` for `Text("secret")`
+fn redact(value: &Value) -> &'static str {
+    match value {
+        Value::Nil => "Nil",
+        Value::Bool(_) => "Bool",
+        Value::Tinyint(_) => "Tinyint",
+        Value::Smallint(_) => "Smallint",
+        Value::Int(_) => "Int",
+        Value::Bigint(_) => "Bigint",
+        Value::Float(_) => "Float",
+        Value::Double(_) => "Double",
+        Value::BigDecimal(_) => "BigDecimal",
+        Value::Blob(_) => "Blob",
+        Value::ImageUri(_) => "ImageUri",
+        Value::Char(_) => "Char",
+        Value::Text(_) => "Text",
+        Value::Json(_) => "Json",
+        Value::Uuid(_) => "Uuid",
+        Value::Date(_) => "Date",
+        Value::Time(_) => "Time",
+        Value::TimeTz(_) => "TimeTz",
+        Value::DateTime(_) => "DateTime",
+        Value::Timestamp(_) => "Timestamp",
+        Value::Interval(_) => "Interval",
+        Value::Inet(_) => "Inet",
+        Value::Cidr(_) => "Cidr",
+        Value::MacAddr(_) => "MacAddr",
+        Value::Point(_) => "Point",
+        Value::Line(_) => "Line",
+        Value::LineSegment(_) => "LineSegment",
+        Value::Box(_) => "Box",
+        Value::Path(_) => "Path",
+        Value::Polygon(_) => "Polygon",
+        Value::Circle(_) => "Circle",
+        Value::Geometry(_) => "Geometry",
+        Value::Range(_) => "Range",
+        Value::Multirange(_) => "Multirange",
+        Value::Composite(_) => "Composite",
+        Value::Array(_) => "Array",
+    }
+}
+
+/// Receives every executed sql, added to the entity manager with `add_observer`.
+/// It is Send so the entity manager holding it can be moved to another thread.
+pub trait QueryObserver: Send {
+    fn on_query(&self, event: &QueryEvent);
+}
+
+/// Logs the sql that took at least the threshold and the ones that failed
+pub struct SlowQueryLogger {
+    pub threshold: Duration,
+}
+
+impl SlowQueryLogger {
+    pub fn new(threshold: Duration) -> Self {
+        SlowQueryLogger { threshold }
+    }
+}
+
+impl QueryObserver for SlowQueryLogger {
+    fn on_query(&self, event: &QueryEvent) {
+        if let Some(e) = event.error {
+            error!(
+                "query failed after {:?}: {} params: {:?} error: {:?}",
+                event.duration,
+                event.sql,
+                event.redacted_params(),
+                e
+            );
+        } else if event.duration >= self.threshold {
+            warn!(
+                "slow query took {:?} returning {} rows: {} params: {:?}",
+                event.duration,
+                event.rows.unwrap_or(0),
+                event.sql,
+                event.redacted_params()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn redact_params() {
+        let text = Value::Text("secret".into());
+        let int = Value::Int(1);
+        let params = [&text, &int, &Value::Nil];
+        let event = QueryEvent::new("SELECT $1, $2, $3", &params, Duration::from_millis(1), Ok(1));
+        assert_eq!(event.param_count(), 3);
+        assert_eq!(event.redacted_params(), vec!["Text", "Int", "Nil"]);
+        assert_eq!(event.rows, Some(1));
+        assert!(event.is_ok());
+    }
+
+    #[test]
+    fn observe_query() {
+        struct Recorder(RefCell<Vec<String>>);
+        impl QueryObserver for Recorder {
+            fn on_query(&self, event: &QueryEvent) {
+                self.0.borrow_mut().push(event.sql.to_string());
+            }
+        }
+        let recorder = Recorder(RefCell::new(vec![]));
+        let error = DbError::UnsupportedOperation("test".into());
+        let event = QueryEvent::new("SELECT 1", &[], Duration::from_secs(2), Err(&error));
+        recorder.on_query(&event);
+        SlowQueryLogger::new(Duration::from_secs(1)).on_query(&event);
+        assert_eq!(*recorder.0.borrow(), vec!["SELECT 1"]);
+        assert!(!event.is_ok());
+        assert_eq!(event.rows, None);
+    }
+}
@@ -0,0 +1,166 @@
+//! A composite is stored as its number of fields,
+//! each as the oid of its type, its length or -1 for null and the binary of its type.
+use super::{OwnedPgValue, PgValue};
+use byteorder::{BigEndian, ReadBytesExt};
+use postgres::types::{private::BytesMut, FromSql, IsNull, Kind, ToSql, Type};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::io::Read;
+use wumn_dao::Value;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgComposite(pub Vec<(String, Value)>);
+
+impl<'a> FromSql<'a> for PgComposite {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+        let fields = match *ty.kind() {
+            Kind::Composite(ref fields) => fields,
+            _ => return Err(format!("{} is not a composite type", ty).into()),
+        };
+        let mut bytes = <&[u8]>::clone(&raw);
+        let count = usize::try_from(bytes.read_i32::<BigEndian>()?)?;
+        if count != fields.len() {
+            return Err(format!("expecting {} fields for {}, got {}", fields.len(), ty, count).into());
+        }
+        let mut values = Vec::with_capacity(count);
+        for field in fields {
+            let _oid = bytes.read_u32::<BigEndian>()?;
+            let len = bytes.read_i32::<BigEndian>()?;
+            let value = if len < 0 {
+                Value::Nil
+            } else {
+                let mut raw = vec![0; usize::try_from(len)?];
+                bytes.read_exact(&mut raw)?;
+                let OwnedPgValue(value) = OwnedPgValue::from_sql(field.type_(), &raw)?;
+                value
+            };
+            values.push((field.name().to_string(), value));
+        }
+        Ok(PgComposite(values))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty.kind(), Kind::Composite(_))
+    }
+}
+
+/// the fields are written in the order of the composite type,
+/// a field missing in the value is null
+impl ToSql for PgComposite {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+        let fields = match *ty.kind() {
+            Kind::Composite(ref fields) => fields,
+            _ => return Err(format!("{} is not a composite type", ty).into()),
+        };
+        if let Some((name, _)) = self
+            .0
+            .iter()
+            .find(|(name, _)| !fields.iter().any(|f| f.name() == name))
+        {
+            return Err(format!("{} has no field {}", ty, name).into());
+        }
+        out.extend_from_slice(&i32::try_from(fields.len())?.to_be_bytes());
+        for field in fields {
+            out.extend_from_slice(&field.type_().oid().to_be_bytes());
+            let value = self
+                .0
+                .iter()
+                .find(|(name, _)| name == field.name())
+                .map(|(_, v)| v)
+                .unwrap_or(&Value::Nil);
+            let start = out.len();
+            out.extend_from_slice(&[0; 4]);
+            let len = match PgValue(value).to_sql(field.type_(), out)? {
+                IsNull::Yes => -1,
+                IsNull::No => i32::try_from(out.len() - start - 4)?,
+            };
+            out[start..start + 4].copy_from_slice(&len.to_be_bytes());
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty.kind(), Kind::Composite(_))
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{DbComposite, ToDao};
+    use postgres::types::Field;
+    use wumn_dao::{ConvertError, DbValue};
+
+    fn address_type() -> Type {
+        let fields = vec![
+            Field::new("street".into(), Type::TEXT),
+            Field::new("zip".into(), Type::INT4),
+        ];
+        Type::new("address".into(), 90_000, Kind::Composite(fields), "public".into())
+    }
+
+    #[derive(Debug, PartialEq, ToDao, DbComposite)]
+    struct Address {
+        street: String,
+        zip: Option<i32>,
+    }
+
+    #[test]
+    fn composite_round_trip() {
+        let ty = address_type();
+        let composite = PgComposite(vec![
+            ("street".into(), Value::Text("Main".into())),
+            ("zip".into(), Value::Nil),
+        ]);
+        let mut out = BytesMut::new();
+        composite.to_sql(&ty, &mut out).unwrap();
+        // the count, then the oid, the length and the bytes of each field
+        assert_eq!(out.len(), 4 + (4 + 4 + 4) + (4 + 4));
+        assert_eq!(PgComposite::from_sql(&ty, &out).unwrap(), composite);
+    }
+
+    #[test]
+    fn composite_missing_and_unknown_fields() {
+        let ty = address_type();
+        let mut out = BytesMut::new();
+        PgComposite(vec![("street".into(), Value::Text("Main".into()))])
+            .to_sql(&ty, &mut out)
+            .unwrap();
+        let read = PgComposite::from_sql(&ty, &out).unwrap();
+        assert_eq!(read.0[1], ("zip".to_string(), Value::Nil));
+        let unknown = PgComposite(vec![("city".into(), Value::Text("Manila".into()))]);
+        assert!(unknown.to_sql(&ty, &mut BytesMut::new()).is_err());
+        assert!(PgComposite::from_sql(&Type::TEXT, &out).is_err());
+    }
+
+    #[test]
+    fn derived_composite() {
+        let address = Address {
+            street: "Main".into(),
+            zip: Some(1000),
+        };
+        let value = Value::from(&address);
+        assert_eq!(
+            value,
+            Value::Composite(vec![
+                ("street".into(), Value::Text("Main".into())),
+                ("zip".into(), Value::Int(1000)),
+            ])
+        );
+        assert_eq!(Address::from_db_value(&value).unwrap(), address);
+        let missing = Value::Composite(vec![("street".into(), Value::Text("Main".into()))]);
+        match Address::from_db_value(&missing) {
+            Err(ConvertError::NotSupported(_, target)) => assert_eq!(target, "Address.zip"),
+            other => panic!("expecting the missing field, got {:?}", other),
+        }
+        let mistyped = Value::Composite(vec![
+            ("street".into(), Value::Int(1)),
+            ("zip".into(), Value::Nil),
+        ]);
+        assert!(Address::from_db_value(&mistyped).is_err());
+    }
+}
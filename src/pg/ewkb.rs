@@ -0,0 +1,363 @@
+//! The geometry and geography of postgis are exchanged in the extended well-known binary,
+//! which is the well-known binary with flags in the type of the geometry for the z and m
+//! dimensions and for a spatial reference identifier following the type.
+//! The z and m dimensions are read and dropped, since the geometries of geo-types are 2D.
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
+use geo_types::{
+    Coord, Geometry, GeometryCollection, LineString, MultiLineString, MultiPoint, MultiPolygon,
+    Point, Polygon,
+};
+use postgres::types::{private::BytesMut, FromSql, IsNull, ToSql, Type};
+use std::convert::TryFrom;
+use std::error::Error;
+use wumn_dao::Spatial;
+
+const WKB_Z: u32 = 0x8000_0000;
+const WKB_M: u32 = 0x4000_0000;
+const WKB_SRID: u32 = 0x2000_0000;
+
+const POINT: u32 = 1;
+const LINE_STRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTI_POINT: u32 = 4;
+const MULTI_LINE_STRING: u32 = 5;
+const MULTI_POLYGON: u32 = 6;
+const GEOMETRY_COLLECTION: u32 = 7;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgSpatial(pub Spatial);
+
+/// the geometry types are defined by the extension, so they are matched by their name
+fn is_spatial(ty: &Type) -> bool {
+    ty.name() == "geometry" || ty.name() == "geography"
+}
+
+impl<'a> FromSql<'a> for PgSpatial {
+    fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+        let mut bytes = <&[u8]>::clone(&raw);
+        let (geometry, srid) = read_geometry(&mut bytes)?;
+        Ok(PgSpatial(Spatial::new(geometry, srid)))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        is_spatial(ty)
+    }
+}
+
+impl ToSql for PgSpatial {
+    fn to_sql(&self, _ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+        let mut bytes = vec![];
+        write_geometry(&mut bytes, &self.0.geometry, self.0.srid)?;
+        out.extend_from_slice(&bytes);
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        is_spatial(ty)
+    }
+
+    to_sql_checked!();
+}
+
+/// reads the numbers in the byte order of the geometry
+struct Reader {
+    little_endian: bool,
+    dimensions: usize,
+}
+
+impl Reader {
+    fn read_u32(&self, bytes: &mut &[u8]) -> Result<u32, BoxError> {
+        let v = if self.little_endian {
+            bytes.read_u32::<LittleEndian>()?
+        } else {
+            bytes.read_u32::<BigEndian>()?
+        };
+        Ok(v)
+    }
+
+    fn read_f64(&self, bytes: &mut &[u8]) -> Result<f64, BoxError> {
+        let v = if self.little_endian {
+            bytes.read_f64::<LittleEndian>()?
+        } else {
+            bytes.read_f64::<BigEndian>()?
+        };
+        Ok(v)
+    }
+
+    fn read_coord(&self, bytes: &mut &[u8]) -> Result<Coord<f64>, BoxError> {
+        let x = self.read_f64(bytes)?;
+        let y = self.read_f64(bytes)?;
+        for _ in 2..self.dimensions {
+            self.read_f64(bytes)?;
+        }
+        Ok(Coord { x, y })
+    }
+
+    fn read_line_string(&self, bytes: &mut &[u8]) -> Result<LineString<f64>, BoxError> {
+        let npoints = self.read_u32(bytes)?;
+        let coords: Result<Vec<_>, _> = (0..npoints).map(|_| self.read_coord(bytes)).collect();
+        Ok(LineString(coords?))
+    }
+
+    fn read_polygon(&self, bytes: &mut &[u8]) -> Result<Polygon<f64>, BoxError> {
+        let nrings = self.read_u32(bytes)?;
+        let mut rings = (0..nrings)
+            .map(|_| self.read_line_string(bytes))
+            .collect::<Result<Vec<_>, _>>()?;
+        if rings.is_empty() {
+            return Ok(Polygon::new(LineString(vec![]), vec![]));
+        }
+        let exterior = rings.remove(0);
+        Ok(Polygon::new(exterior, rings))
+    }
+}
+
+/// the geometry and its srid, the geometries inside a collection have no srid of their own
+fn read_geometry(bytes: &mut &[u8]) -> Result<(Geometry<f64>, Option<i32>), BoxError> {
+    let little_endian = bytes.read_u8()? == 1;
+    let mut reader = Reader {
+        little_endian,
+        dimensions: 2,
+    };
+    let ty = reader.read_u32(bytes)?;
+    let srid = if ty & WKB_SRID != 0 {
+        Some(reader.read_u32(bytes)? as i32)
+    } else {
+        None
+    };
+    // the iso variant adds 1000 for z, 2000 for m and 3000 for both to the code
+    let iso = ty & 0x0fff_ffff;
+    reader.dimensions += match iso / 1000 {
+        1 | 2 => 1,
+        3 => 2,
+        _ => 0,
+    };
+    if ty & WKB_Z != 0 {
+        reader.dimensions += 1;
+    }
+    if ty & WKB_M != 0 {
+        reader.dimensions += 1;
+    }
+    let geometry = match iso % 1000 {
+        POINT => Geometry::Point(Point(reader.read_coord(bytes)?)),
+        LINE_STRING => Geometry::LineString(reader.read_line_string(bytes)?),
+        POLYGON => Geometry::Polygon(reader.read_polygon(bytes)?),
+        MULTI_POINT => {
+            let points = read_parts(&reader, bytes, |g| match g {
+                Geometry::Point(v) => Some(v),
+                _ => None,
+            })?;
+            Geometry::MultiPoint(MultiPoint(points))
+        }
+        MULTI_LINE_STRING => {
+            let line_strings = read_parts(&reader, bytes, |g| match g {
+                Geometry::LineString(v) => Some(v),
+                _ => None,
+            })?;
+            Geometry::MultiLineString(MultiLineString(line_strings))
+        }
+        MULTI_POLYGON => {
+            let polygons = read_parts(&reader, bytes, |g| match g {
+                Geometry::Polygon(v) => Some(v),
+                _ => None,
+            })?;
+            Geometry::MultiPolygon(MultiPolygon(polygons))
+        }
+        GEOMETRY_COLLECTION => {
+            let geometries = read_parts(&reader, bytes, Some)?;
+            Geometry::GeometryCollection(GeometryCollection(geometries))
+        }
+        code => return Err(format!("unsupported geometry type {}", code).into()),
+    };
+    Ok((geometry, srid))
+}
+
+/// the geometries of a multi geometry, each with its own header
+fn read_parts<T, F>(reader: &Reader, bytes: &mut &[u8], part: F) -> Result<Vec<T>, BoxError>
+where
+    F: Fn(Geometry<f64>) -> Option<T>,
+{
+    let count = reader.read_u32(bytes)?;
+    (0..count)
+        .map(|_| {
+            let (geometry, _srid) = read_geometry(bytes)?;
+            part(geometry).ok_or_else(|| "unexpected geometry in a multi geometry".into())
+        })
+        .collect()
+}
+
+fn write_u32(bytes: &mut Vec<u8>, v: usize) -> Result<(), BoxError> {
+    bytes.write_u32::<LittleEndian>(u32::try_from(v)?)?;
+    Ok(())
+}
+
+fn write_coords(bytes: &mut Vec<u8>, coords: &[Coord<f64>]) -> Result<(), BoxError> {
+    write_u32(bytes, coords.len())?;
+    for coord in coords {
+        bytes.write_f64::<LittleEndian>(coord.x)?;
+        bytes.write_f64::<LittleEndian>(coord.y)?;
+    }
+    Ok(())
+}
+
+fn write_polygon(bytes: &mut Vec<u8>, polygon: &Polygon<f64>) -> Result<(), BoxError> {
+    let empty = polygon.exterior().0.is_empty();
+    write_u32(bytes, if empty { 0 } else { 1 + polygon.interiors().len() })?;
+    if !empty {
+        write_coords(bytes, &polygon.exterior().0)?;
+        for interior in polygon.interiors() {
+            write_coords(bytes, &interior.0)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_header(bytes: &mut Vec<u8>, ty: u32, srid: Option<i32>) -> Result<(), BoxError> {
+    bytes.write_u8(1)?;
+    match srid {
+        Some(srid) => {
+            bytes.write_u32::<LittleEndian>(ty | WKB_SRID)?;
+            bytes.write_i32::<LittleEndian>(srid)?;
+        }
+        None => bytes.write_u32::<LittleEndian>(ty)?,
+    }
+    Ok(())
+}
+
+/// written in little endian, a line and a rectangle are written as a linestring and a polygon
+fn write_geometry(
+    bytes: &mut Vec<u8>,
+    geometry: &Geometry<f64>,
+    srid: Option<i32>,
+) -> Result<(), BoxError> {
+    match *geometry {
+        Geometry::Point(ref v) => {
+            write_header(bytes, POINT, srid)?;
+            bytes.write_f64::<LittleEndian>(v.x())?;
+            bytes.write_f64::<LittleEndian>(v.y())?;
+        }
+        Geometry::Line(ref v) => {
+            write_header(bytes, LINE_STRING, srid)?;
+            write_coords(bytes, &[v.start, v.end])?;
+        }
+        Geometry::LineString(ref v) => {
+            write_header(bytes, LINE_STRING, srid)?;
+            write_coords(bytes, &v.0)?;
+        }
+        Geometry::Polygon(ref v) => {
+            write_header(bytes, POLYGON, srid)?;
+            write_polygon(bytes, v)?;
+        }
+        Geometry::Rect(ref v) => {
+            write_header(bytes, POLYGON, srid)?;
+            write_polygon(bytes, &v.to_polygon())?;
+        }
+        Geometry::Triangle(ref v) => {
+            write_header(bytes, POLYGON, srid)?;
+            write_polygon(bytes, &v.to_polygon())?;
+        }
+        Geometry::MultiPoint(ref v) => {
+            write_header(bytes, MULTI_POINT, srid)?;
+            write_u32(bytes, v.0.len())?;
+            for point in v.0.iter() {
+                write_geometry(bytes, &Geometry::Point(*point), None)?;
+            }
+        }
+        Geometry::MultiLineString(ref v) => {
+            write_header(bytes, MULTI_LINE_STRING, srid)?;
+            write_u32(bytes, v.0.len())?;
+            for line_string in v.0.iter() {
+                write_header(bytes, LINE_STRING, None)?;
+                write_coords(bytes, &line_string.0)?;
+            }
+        }
+        Geometry::MultiPolygon(ref v) => {
+            write_header(bytes, MULTI_POLYGON, srid)?;
+            write_u32(bytes, v.0.len())?;
+            for polygon in v.0.iter() {
+                write_header(bytes, POLYGON, None)?;
+                write_polygon(bytes, polygon)?;
+            }
+        }
+        Geometry::GeometryCollection(ref v) => {
+            write_header(bytes, GEOMETRY_COLLECTION, srid)?;
+            write_u32(bytes, v.0.len())?;
+            for geometry in v.0.iter() {
+                write_geometry(bytes, geometry, None)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn point_with_srid() {
+        // SRID=4326;POINT(1 2)
+        let ewkb = from_hex("0101000020E6100000000000000000F03F0000000000000040");
+        let (geometry, srid) = read_geometry(&mut ewkb.as_slice()).unwrap();
+        assert_eq!(geometry, Geometry::Point(Point::new(1.0, 2.0)));
+        assert_eq!(srid, Some(4326));
+        let mut bytes = vec![];
+        write_geometry(&mut bytes, &geometry, srid).unwrap();
+        assert_eq!(bytes, ewkb);
+    }
+
+    #[test]
+    fn srid_flag() {
+        let header = |bytes: &[u8]| u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]);
+        let point = Geometry::Point(Point::new(1.0, 2.0));
+        let mut bytes = vec![];
+        write_geometry(&mut bytes, &point, None).unwrap();
+        assert_eq!(header(&bytes), POINT);
+        assert_eq!(bytes.len(), 1 + 4 + 16);
+        assert_eq!(read_geometry(&mut bytes.as_slice()).unwrap(), (point.clone(), None));
+        // the srid follows the type, only in the outer geometry
+        let multi = Geometry::MultiPoint(MultiPoint(vec![Point::new(1.0, 2.0)]));
+        let mut bytes = vec![];
+        write_geometry(&mut bytes, &multi, Some(4326)).unwrap();
+        assert_eq!(header(&bytes), MULTI_POINT | WKB_SRID);
+        assert_eq!(bytes[5..9].to_vec(), 4326i32.to_le_bytes().to_vec());
+        assert_eq!(header(&bytes[13..]), POINT);
+        assert_eq!(read_geometry(&mut bytes.as_slice()).unwrap(), (multi, Some(4326)));
+    }
+
+    #[test]
+    fn big_endian_point_z() {
+        // POINT Z (1 2 3) in big endian
+        let ewkb = from_hex("00800000013FF000000000000040000000000000004008000000000000");
+        let (geometry, srid) = read_geometry(&mut ewkb.as_slice()).unwrap();
+        assert_eq!(geometry, Geometry::Point(Point::new(1.0, 2.0)));
+        assert_eq!(srid, None);
+    }
+
+    #[test]
+    fn collection_round_trip() {
+        let square = Polygon::new(
+            LineString::from(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)]),
+            vec![],
+        );
+        let geometry = Geometry::GeometryCollection(GeometryCollection(vec![
+            Geometry::MultiPolygon(MultiPolygon(vec![square.clone(), square])),
+            Geometry::LineString(LineString::from(vec![(0.0, 0.0), (2.0, 3.0)])),
+            Geometry::MultiPoint(MultiPoint(vec![Point::new(5.0, 6.0)])),
+        ]));
+        let mut bytes = vec![];
+        write_geometry(&mut bytes, &geometry, Some(3857)).unwrap();
+        let (read, srid) = read_geometry(&mut bytes.as_slice()).unwrap();
+        assert_eq!(read, geometry);
+        assert_eq!(srid, Some(3857));
+    }
+}
@@ -0,0 +1,272 @@
+//! The geometric types of postgresql are stored as 64 bit floats,
+//! the path and the polygon are prefixed with their number of points.
+//! `point` and `box` are converted by the postgres crate itself.
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use geo_types::{Coord, Line, LineString, Point, Polygon};
+use postgres::types::{self, private::BytesMut, FromSql, IsNull, ToSql, Type};
+use std::convert::TryFrom;
+use std::error::Error;
+use wumn_dao::{Circle, LineEquation};
+
+fn read_coord(bytes: &mut &[u8]) -> Result<Coord<f64>, Box<dyn Error + Send + Sync>> {
+    let x = bytes.read_f64::<BigEndian>()?;
+    let y = bytes.read_f64::<BigEndian>()?;
+    Ok(Coord { x, y })
+}
+
+fn read_coords(bytes: &mut &[u8]) -> Result<Vec<Coord<f64>>, Box<dyn Error + Send + Sync>> {
+    let npts = bytes.read_i32::<BigEndian>()?;
+    (0..npts).map(|_| read_coord(bytes)).collect()
+}
+
+fn write_coord(bytes: &mut Vec<u8>, coord: Coord<f64>) -> Result<(), Box<dyn Error + Send + Sync>> {
+    bytes.write_f64::<BigEndian>(coord.x)?;
+    bytes.write_f64::<BigEndian>(coord.y)?;
+    Ok(())
+}
+
+fn write_coords(bytes: &mut Vec<u8>, coords: &[Coord<f64>]) -> Result<(), Box<dyn Error + Send + Sync>> {
+    bytes.write_i32::<BigEndian>(i32::try_from(coords.len())?)?;
+    for coord in coords {
+        write_coord(bytes, *coord)?;
+    }
+    Ok(())
+}
+
+/// the points without the last one when it closes the line back to the first point,
+/// a closed path of a single point is that point twice
+fn open_coords(line_string: &LineString<f64>) -> &[Coord<f64>] {
+    let coords = &line_string.0;
+    if coords.len() > 1 && line_string.is_closed() {
+        &coords[..coords.len() - 1]
+    } else {
+        coords
+    }
+}
+
+macro_rules! impl_geometric {
+    ($pg:ident, $ty:ty, $pg_type:ident, |$bytes:ident| $read:expr, |$v:ident, $out:ident| $write:expr) => {
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct $pg(pub $ty);
+
+        impl<'a> FromSql<'a> for $pg {
+            fn from_sql(_ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+                let mut bytes = <&[u8]>::clone(&raw);
+                let $bytes = &mut bytes;
+                Ok($pg($read))
+            }
+
+            fn accepts(ty: &Type) -> bool {
+                matches!(*ty, types::Type::$pg_type)
+            }
+        }
+
+        impl ToSql for $pg {
+            fn to_sql(
+                &self,
+                _ty: &Type,
+                out: &mut BytesMut,
+            ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+                let $v = &self.0;
+                let mut bytes = vec![];
+                let $out = &mut bytes;
+                $write;
+                out.extend_from_slice(&bytes);
+                Ok(IsNull::No)
+            }
+
+            fn accepts(ty: &Type) -> bool {
+                matches!(*ty, types::Type::$pg_type)
+            }
+
+            to_sql_checked!();
+        }
+    };
+}
+
+// the infinite line of the equation `a*x + b*y + c = 0`
+impl_geometric!(
+    PgLine,
+    LineEquation,
+    LINE,
+    |bytes| {
+        let a = bytes.read_f64::<BigEndian>()?;
+        let b = bytes.read_f64::<BigEndian>()?;
+        let c = bytes.read_f64::<BigEndian>()?;
+        LineEquation::new(a, b, c)
+    },
+    |v, out| {
+        out.write_f64::<BigEndian>(v.a)?;
+        out.write_f64::<BigEndian>(v.b)?;
+        out.write_f64::<BigEndian>(v.c)?;
+    }
+);
+
+impl_geometric!(
+    PgLineSegment,
+    Line<f64>,
+    LSEG,
+    |bytes| Line::new(read_coord(bytes)?, read_coord(bytes)?),
+    |v, out| {
+        write_coord(out, v.start)?;
+        write_coord(out, v.end)?;
+    }
+);
+
+// a closed path ends with its first point, as a closed line string does
+impl_geometric!(
+    PgPath,
+    LineString<f64>,
+    PATH,
+    |bytes| {
+        let closed = bytes.read_u8()? != 0;
+        let mut coords = read_coords(bytes)?;
+        if closed && !coords.is_empty() {
+            coords.push(coords[0]);
+        }
+        LineString(coords)
+    },
+    |v, out| {
+        let coords = open_coords(v);
+        out.write_u8(u8::from(coords.len() < v.0.len()))?;
+        write_coords(out, coords)?;
+    }
+);
+
+// a polygon of postgresql has no holes
+impl_geometric!(
+    PgPolygon,
+    Polygon<f64>,
+    POLYGON,
+    |bytes| Polygon::new(LineString(read_coords(bytes)?), vec![]),
+    |v, out| {
+        if !v.interiors().is_empty() {
+            return Err("a polygon of postgresql can not have interior rings".into());
+        }
+        write_coords(out, open_coords(v.exterior()))?;
+    }
+);
+
+impl_geometric!(
+    PgCircle,
+    Circle,
+    CIRCLE,
+    |bytes| {
+        let center = read_coord(bytes)?;
+        let radius = bytes.read_f64::<BigEndian>()?;
+        Circle::new(Point(center), radius)
+    },
+    |v, out| {
+        write_coord(out, v.center.0)?;
+        out.write_f64::<BigEndian>(v.radius)?;
+    }
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::{Coord, Rect};
+
+    fn encode<T: ToSql>(value: &T, ty: &Type) -> Vec<u8> {
+        let mut out = BytesMut::new();
+        value.to_sql(ty, &mut out).unwrap();
+        out.to_vec()
+    }
+
+    /// the big endian bytes of these floats
+    fn floats(values: &[f64]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_be_bytes().to_vec()).collect()
+    }
+
+    /// the bytes of a path or polygon with these points
+    fn points(prefix: &[u8], coords: &[(f64, f64)]) -> Vec<u8> {
+        let mut bytes = prefix.to_vec();
+        bytes.extend_from_slice(&(coords.len() as i32).to_be_bytes());
+        for &(x, y) in coords {
+            bytes.extend(floats(&[x, y]));
+        }
+        bytes
+    }
+
+    #[test]
+    fn point_and_box() {
+        let point = Point::new(1.5, -2.0);
+        let bytes = encode(&point, &Type::POINT);
+        assert_eq!(bytes, floats(&[1.5, -2.0]));
+        assert_eq!(Point::<f64>::from_sql(&Type::POINT, &bytes).unwrap(), point);
+        let rect = Rect::new(Coord { x: 0.0, y: 0.0 }, Coord { x: 2.0, y: 3.0 });
+        let bytes = encode(&rect, &Type::BOX);
+        // the server orders the corners of the box it receives
+        assert_eq!(bytes, floats(&[0.0, 0.0, 2.0, 3.0]));
+        let high_first = floats(&[2.0, 3.0, 0.0, 0.0]);
+        assert_eq!(Rect::<f64>::from_sql(&Type::BOX, &high_first).unwrap(), rect);
+        assert_eq!(Rect::<f64>::from_sql(&Type::BOX, &bytes).unwrap(), rect);
+    }
+
+    #[test]
+    fn line_and_line_segment() {
+        let line = PgLine(LineEquation::new(1.0, -1.0, 0.5));
+        let bytes = encode(&line, &Type::LINE);
+        assert_eq!(bytes, floats(&[1.0, -1.0, 0.5]));
+        assert_eq!(PgLine::from_sql(&Type::LINE, &bytes).unwrap(), line);
+        let lseg = PgLineSegment(Line::new(Coord { x: 0.0, y: 1.0 }, Coord { x: 2.0, y: 3.0 }));
+        let bytes = encode(&lseg, &Type::LSEG);
+        assert_eq!(bytes, floats(&[0.0, 1.0, 2.0, 3.0]));
+        assert_eq!(PgLineSegment::from_sql(&Type::LSEG, &bytes).unwrap(), lseg);
+    }
+
+    #[test]
+    fn open_and_closed_path() {
+        let open = points(&[0], &[(0.0, 0.0), (1.0, 1.0), (0.0, 0.0)]);
+        let path = PgPath::from_sql(&Type::PATH, &open).unwrap();
+        assert_eq!(path.0, LineString::from(vec![(0.0, 0.0), (1.0, 1.0), (0.0, 0.0)]));
+        // a line string which ends at its first point is written as a closed path
+        assert_eq!(encode(&path, &Type::PATH), points(&[1], &[(0.0, 0.0), (1.0, 1.0)]));
+        let closed = points(&[1], &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)]);
+        let path = PgPath::from_sql(&Type::PATH, &closed).unwrap();
+        assert_eq!(
+            path.0,
+            LineString::from(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)])
+        );
+        assert_eq!(encode(&path, &Type::PATH), closed);
+        let line = points(&[0], &[(0.0, 0.0), (1.0, 1.0)]);
+        let path = PgPath::from_sql(&Type::PATH, &line).unwrap();
+        assert_eq!(encode(&path, &Type::PATH), line);
+    }
+
+    #[test]
+    fn closed_path_of_one_point() {
+        let closed = points(&[1], &[(2.0, 3.0)]);
+        let path = PgPath::from_sql(&Type::PATH, &closed).unwrap();
+        assert_eq!(path.0, LineString::from(vec![(2.0, 3.0), (2.0, 3.0)]));
+        assert_eq!(encode(&path, &Type::PATH), closed);
+        let empty = points(&[1], &[]);
+        let path = PgPath::from_sql(&Type::PATH, &empty).unwrap();
+        assert_eq!(path.0, LineString(vec![]));
+        assert_eq!(encode(&path, &Type::PATH), points(&[0], &[]));
+    }
+
+    #[test]
+    fn polygon() {
+        let bytes = points(&[], &[(0.0, 0.0), (4.0, 0.0), (0.0, 3.0)]);
+        let polygon = PgPolygon::from_sql(&Type::POLYGON, &bytes).unwrap();
+        assert_eq!(
+            polygon.0.exterior(),
+            &LineString::from(vec![(0.0, 0.0), (4.0, 0.0), (0.0, 3.0), (0.0, 0.0)])
+        );
+        assert_eq!(encode(&polygon, &Type::POLYGON), bytes);
+        let hole = LineString::from(vec![(1.0, 1.0), (2.0, 1.0), (1.0, 2.0)]);
+        let with_hole = PgPolygon(Polygon::new(polygon.0.exterior().clone(), vec![hole]));
+        let mut out = BytesMut::new();
+        assert!(with_hole.to_sql(&Type::POLYGON, &mut out).is_err());
+    }
+
+    #[test]
+    fn circle() {
+        let circle = PgCircle(Circle::new(Point::new(1.0, 2.0), 3.0));
+        let bytes = encode(&circle, &Type::CIRCLE);
+        assert_eq!(bytes, floats(&[1.0, 2.0, 3.0]));
+        assert_eq!(PgCircle::from_sql(&Type::CIRCLE, &bytes).unwrap(), circle);
+        assert!(PgCircle::from_sql(&Type::CIRCLE, &bytes[..16]).is_err());
+    }
+}
@@ -5,8 +5,8 @@
 /// representing number of months. This struct is a dumb wrapper type, meant only to indicate the
 /// meaning of these parts.
 ///
-use byteorder::{BigEndian, ReadBytesExt};
-use postgres::types::{self, FromSql, Type};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use postgres::types::{self, private::BytesMut, FromSql, IsNull, ToSql, Type};
 use std::error::Error;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -76,3 +76,38 @@ impl<'a> FromSql<'a> for PgInterval {
         }
     }
 }
+
+impl ToSql for PgInterval {
+    fn to_sql(
+        &self,
+        _ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let mut bytes = Vec::with_capacity(16);
+        bytes.write_i64::<BigEndian>(self.microseconds)?;
+        bytes.write_i32::<BigEndian>(self.days)?;
+        bytes.write_i32::<BigEndian>(self.months)?;
+        out.extend_from_slice(&bytes);
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, types::Type::INTERVAL)
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn interval_round_trip() {
+        let interval = PgInterval::new(3_600_000_001, -2, 14);
+        let mut out = BytesMut::new();
+        interval.to_sql(&Type::INTERVAL, &mut out).unwrap();
+        assert_eq!(out.len(), 16);
+        assert_eq!(PgInterval::from_sql(&Type::INTERVAL, &out).unwrap(), interval);
+    }
+}
@@ -0,0 +1,106 @@
+//! `inet` and `cidr` are stored as the address family, the prefix length, a cidr flag,
+//! the number of bytes of the address followed by the address.
+//! `macaddr` is stored as its 6 bytes.
+use byteorder::{ReadBytesExt, WriteBytesExt};
+use postgres::types::{self, private::BytesMut, FromSql, IsNull, ToSql, Type};
+use std::error::Error;
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use wumn_dao::{IpNetwork, MacAddr};
+
+/// the address families of postgresql, which are not the same as the ones of the os
+const PGSQL_AF_INET: u8 = 2;
+const PGSQL_AF_INET6: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PgIpNetwork(pub IpNetwork);
+
+impl<'a> FromSql<'a> for PgIpNetwork {
+    fn from_sql(_ty: &Type, bytes: &'a [u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut bytes = <&[u8]>::clone(&bytes);
+        let family = bytes.read_u8()?;
+        let prefix = bytes.read_u8()?;
+        let _is_cidr = bytes.read_u8()?;
+        let _length = bytes.read_u8()?;
+        let addr = match family {
+            PGSQL_AF_INET => {
+                let mut octets = [0; 4];
+                bytes.read_exact(&mut octets)?;
+                IpAddr::V4(Ipv4Addr::from(octets))
+            }
+            PGSQL_AF_INET6 => {
+                let mut octets = [0; 16];
+                bytes.read_exact(&mut octets)?;
+                IpAddr::V6(Ipv6Addr::from(octets))
+            }
+            _ => return Err(format!("unknown address family {}", family).into()),
+        };
+        let network = IpNetwork::new(addr, prefix).ok_or("network prefix is out of range")?;
+        Ok(PgIpNetwork(network))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, types::Type::INET | types::Type::CIDR)
+    }
+}
+
+/// the cidr flag is set from the type of the column
+impl ToSql for PgIpNetwork {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let mut bytes = Vec::with_capacity(20);
+        let (family, octets) = match self.0.addr {
+            IpAddr::V4(addr) => (PGSQL_AF_INET, addr.octets().to_vec()),
+            IpAddr::V6(addr) => (PGSQL_AF_INET6, addr.octets().to_vec()),
+        };
+        bytes.write_u8(family)?;
+        bytes.write_u8(self.0.prefix)?;
+        bytes.write_u8(u8::from(*ty == types::Type::CIDR))?;
+        bytes.write_u8(octets.len() as u8)?;
+        bytes.extend_from_slice(&octets);
+        out.extend_from_slice(&bytes);
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, types::Type::INET | types::Type::CIDR)
+    }
+
+    to_sql_checked!();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PgMacAddr(pub MacAddr);
+
+impl<'a> FromSql<'a> for PgMacAddr {
+    fn from_sql(_ty: &Type, bytes: &'a [u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut bytes = <&[u8]>::clone(&bytes);
+        let mut octets = [0; 6];
+        bytes.read_exact(&mut octets)?;
+        Ok(PgMacAddr(MacAddr(octets)))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, types::Type::MACADDR)
+    }
+}
+
+impl ToSql for PgMacAddr {
+    fn to_sql(
+        &self,
+        _ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        out.extend_from_slice(&(self.0).0);
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, types::Type::MACADDR)
+    }
+
+    to_sql_checked!();
+}
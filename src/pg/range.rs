@@ -0,0 +1,267 @@
+//! A range is stored as its flags followed by the bounds which have a value,
+//! each as its length and the binary of the element type.
+//! A multirange is stored as the number of its ranges, each with its length.
+use super::{OwnedPgValue, PgValue};
+use byteorder::{BigEndian, ReadBytesExt};
+use postgres::types::{private::BytesMut, FromSql, IsNull, Kind, ToSql, Type};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::io::Read;
+use std::ops::Bound;
+use wumn_dao::{Range, Value};
+
+const RANGE_EMPTY: u8 = 0x01;
+const RANGE_LB_INC: u8 = 0x02;
+const RANGE_UB_INC: u8 = 0x04;
+const RANGE_LB_INF: u8 = 0x08;
+const RANGE_UB_INF: u8 = 0x10;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgRange(pub Range<Value>);
+
+fn element_type(ty: &Type) -> Result<&Type, BoxError> {
+    match *ty.kind() {
+        Kind::Range(ref element) => Ok(element),
+        _ => Err(format!("{} is not a range type", ty).into()),
+    }
+}
+
+fn read_bound(
+    element: &Type,
+    bytes: &mut &[u8],
+    inclusive: bool,
+) -> Result<Bound<Value>, BoxError> {
+    let len = usize::try_from(bytes.read_i32::<BigEndian>()?)?;
+    let mut raw = vec![0; len];
+    bytes.read_exact(&mut raw)?;
+    let OwnedPgValue(value) = OwnedPgValue::from_sql(element, &raw)?;
+    Ok(if inclusive {
+        Bound::Included(value)
+    } else {
+        Bound::Excluded(value)
+    })
+}
+
+/// the bound as its length followed by the binary of the element
+fn write_bound(element: &Type, value: &Value, out: &mut BytesMut) -> Result<(), BoxError> {
+    let start = out.len();
+    out.extend_from_slice(&[0; 4]);
+    PgValue(value).to_sql(element, out)?;
+    let len = i32::try_from(out.len() - start - 4)?;
+    out[start..start + 4].copy_from_slice(&len.to_be_bytes());
+    Ok(())
+}
+
+/// a null bound is unbounded
+fn bound_value(bound: &Bound<Value>) -> Option<&Value> {
+    match *bound {
+        Bound::Included(ref v) | Bound::Excluded(ref v) if !v.is_nil() => Some(v),
+        _ => None,
+    }
+}
+
+fn read_range(element: &Type, raw: &[u8]) -> Result<Range<Value>, BoxError> {
+    let mut bytes = <&[u8]>::clone(&raw);
+    let flags = bytes.read_u8()?;
+    if flags & RANGE_EMPTY != 0 {
+        return Ok(Range::empty());
+    }
+    let lower = if flags & RANGE_LB_INF != 0 {
+        Bound::Unbounded
+    } else {
+        read_bound(element, &mut bytes, flags & RANGE_LB_INC != 0)?
+    };
+    let upper = if flags & RANGE_UB_INF != 0 {
+        Bound::Unbounded
+    } else {
+        read_bound(element, &mut bytes, flags & RANGE_UB_INC != 0)?
+    };
+    Ok(Range::new(lower, upper))
+}
+
+impl<'a> FromSql<'a> for PgRange {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+        read_range(element_type(ty)?, raw).map(PgRange)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty.kind(), Kind::Range(_))
+    }
+}
+
+fn write_range(range: &Range<Value>, element: &Type, out: &mut BytesMut) -> Result<(), BoxError> {
+    if range.empty {
+        out.extend_from_slice(&[RANGE_EMPTY]);
+        return Ok(());
+    }
+    let lower = bound_value(&range.lower);
+    let upper = bound_value(&range.upper);
+    let mut flags = 0;
+    match lower {
+        Some(_) if matches!(range.lower, Bound::Included(_)) => flags |= RANGE_LB_INC,
+        Some(_) => {}
+        None => flags |= RANGE_LB_INF,
+    }
+    match upper {
+        Some(_) if matches!(range.upper, Bound::Included(_)) => flags |= RANGE_UB_INC,
+        Some(_) => {}
+        None => flags |= RANGE_UB_INF,
+    }
+    out.extend_from_slice(&[flags]);
+    for value in lower.into_iter().chain(upper) {
+        write_bound(element, value, out)?;
+    }
+    Ok(())
+}
+
+impl ToSql for PgRange {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+        write_range(&self.0, element_type(ty)?, out)?;
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty.kind(), Kind::Range(_))
+    }
+
+    to_sql_checked!();
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgMultirange(pub Vec<Range<Value>>);
+
+/// the element type of the ranges of a multirange
+fn multirange_element_type(ty: &Type) -> Result<&Type, BoxError> {
+    match *ty.kind() {
+        Kind::Multirange(ref element) => Ok(element),
+        _ => Err(format!("{} is not a multirange type", ty).into()),
+    }
+}
+
+impl<'a> FromSql<'a> for PgMultirange {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+        let element = multirange_element_type(ty)?;
+        let mut bytes = <&[u8]>::clone(&raw);
+        let count = usize::try_from(bytes.read_i32::<BigEndian>()?)?;
+        // each range takes at least the 4 bytes of its length
+        let mut ranges = Vec::with_capacity(count.min(bytes.len() / 4));
+        for _ in 0..count {
+            let len = usize::try_from(bytes.read_i32::<BigEndian>()?)?;
+            let mut raw = vec![0; len];
+            bytes.read_exact(&mut raw)?;
+            ranges.push(read_range(element, &raw)?);
+        }
+        Ok(PgMultirange(ranges))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty.kind(), Kind::Multirange(_))
+    }
+}
+
+impl ToSql for PgMultirange {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, BoxError> {
+        let element = multirange_element_type(ty)?;
+        out.extend_from_slice(&i32::try_from(self.0.len())?.to_be_bytes());
+        for range in self.0.iter() {
+            let start = out.len();
+            out.extend_from_slice(&[0; 4]);
+            write_range(range, element, out)?;
+            let len = i32::try_from(out.len() - start - 4)?;
+            out[start..start + 4].copy_from_slice(&len.to_be_bytes());
+        }
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty.kind(), Kind::Multirange(_))
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode<T: ToSql>(value: &T, ty: &Type) -> Vec<u8> {
+        let mut out = BytesMut::new();
+        value.to_sql(ty, &mut out).unwrap();
+        out.to_vec()
+    }
+
+    /// the bound of an int4 as its length and its big endian bytes
+    fn int_bound(v: i32) -> Vec<u8> {
+        let mut bytes = 4i32.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&v.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn bounded_range() {
+        let range = PgRange(Range::from(Value::Int(1)..Value::Int(5)));
+        let bytes = encode(&range, &Type::INT4_RANGE);
+        let mut expected = vec![RANGE_LB_INC];
+        expected.extend(int_bound(1));
+        expected.extend(int_bound(5));
+        assert_eq!(bytes, expected);
+        assert_eq!(PgRange::from_sql(&Type::INT4_RANGE, &bytes).unwrap(), range);
+    }
+
+    #[test]
+    fn empty_range() {
+        let range = PgRange(Range::empty());
+        let bytes = encode(&range, &Type::INT4_RANGE);
+        assert_eq!(bytes, vec![RANGE_EMPTY]);
+        assert_eq!(PgRange::from_sql(&Type::INT4_RANGE, &bytes).unwrap(), range);
+    }
+
+    #[test]
+    fn unbounded_range() {
+        let range = PgRange(Range::new(Bound::Excluded(Value::Int(3)), Bound::Unbounded));
+        let bytes = encode(&range, &Type::INT4_RANGE);
+        let mut expected = vec![RANGE_UB_INF];
+        expected.extend(int_bound(3));
+        assert_eq!(bytes, expected);
+        assert_eq!(PgRange::from_sql(&Type::INT4_RANGE, &bytes).unwrap(), range);
+        // a null bound is unbounded too
+        let null = PgRange(Range::new(Bound::Included(Value::Nil), Bound::Unbounded));
+        assert_eq!(encode(&null, &Type::INT4_RANGE), vec![RANGE_LB_INF | RANGE_UB_INF]);
+    }
+
+    #[test]
+    fn multirange() {
+        let multirange = PgMultirange(vec![
+            Range::from(Value::Int(1)..Value::Int(3)),
+            Range::new(Bound::Included(Value::Int(7)), Bound::Unbounded),
+        ]);
+        let bytes = encode(&multirange, &Type::INT4MULTI_RANGE);
+        let mut expected = 2i32.to_be_bytes().to_vec();
+        expected.extend(17i32.to_be_bytes());
+        expected.push(RANGE_LB_INC);
+        expected.extend(int_bound(1));
+        expected.extend(int_bound(3));
+        expected.extend(9i32.to_be_bytes());
+        expected.push(RANGE_LB_INC | RANGE_UB_INF);
+        expected.extend(int_bound(7));
+        assert_eq!(bytes, expected);
+        assert_eq!(
+            PgMultirange::from_sql(&Type::INT4MULTI_RANGE, &bytes).unwrap(),
+            multirange
+        );
+        let empty = encode(&PgMultirange(vec![]), &Type::INT4MULTI_RANGE);
+        assert_eq!(empty, 0i32.to_be_bytes().to_vec());
+        assert_eq!(
+            PgMultirange::from_sql(&Type::INT4MULTI_RANGE, &empty).unwrap(),
+            PgMultirange(vec![])
+        );
+    }
+
+    #[test]
+    fn multirange_count_beyond_its_bytes() {
+        let bytes = i32::MAX.to_be_bytes().to_vec();
+        assert!(PgMultirange::from_sql(&Type::INT4MULTI_RANGE, &bytes).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+//! module statement_cache keeps the prepared statements of a connection keyed by their sql,
+//! evicting the least recently used one when the cache is full.
+use postgres::Statement;
+use std::collections::HashMap;
+
+/// the number of prepared statements cached per connection when not configured
+pub const DEFAULT_STATEMENT_CACHE_SIZE: usize = 100;
+
+pub struct StatementCache<S = Statement> {
+    capacity: usize,
+    /// the statement and the tick of when it was last used
+    statements: HashMap<String, (S, u64)>,
+    tick: u64,
+}
+
+impl<S: Clone> StatementCache<S> {
+    /// a cache of this number of statements, 0 disables the caching
+    pub fn new(capacity: usize) -> Self {
+        StatementCache {
+            capacity,
+            statements: HashMap::with_capacity(capacity),
+            tick: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    pub fn get(&mut self, sql: &str) -> Option<S> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.statements.get_mut(sql).map(|(stmt, used)| {
+            *used = tick;
+            stmt.clone()
+        })
+    }
+
+    pub fn insert(&mut self, sql: &str, stmt: S) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.statements.len() >= self.capacity && !self.statements.contains_key(sql) {
+            self.evict_least_recently_used();
+        }
+        self.tick += 1;
+        self.statements.insert(sql.to_string(), (stmt, self.tick));
+    }
+
+    pub fn remove(&mut self, sql: &str) {
+        self.statements.remove(sql);
+    }
+
+    /// drop all the statements, such as when the schema changed
+    pub fn clear(&mut self) {
+        self.statements.clear();
+    }
+
+    /// change the number of statements kept, evicting the least recently used ones
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.statements.len() > capacity {
+            self.evict_least_recently_used();
+        }
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        let lru = self
+            .statements
+            .iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(sql, _)| sql.to_string());
+        if let Some(lru) = lru {
+            self.statements.remove(&lru);
+        }
+    }
+}
+
+impl<S: Clone> Default for StatementCache<S> {
+    fn default() -> Self {
+        StatementCache::new(DEFAULT_STATEMENT_CACHE_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn evict_least_recently_used() {
+        let mut cache: StatementCache<&str> = StatementCache::new(2);
+        cache.insert("SELECT 1", "one");
+        cache.insert("SELECT 2", "two");
+        assert_eq!(cache.get("SELECT 1"), Some("one"));
+        cache.insert("SELECT 3", "three");
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("SELECT 2"), None);
+        assert_eq!(cache.get("SELECT 1"), Some("one"));
+        assert_eq!(cache.get("SELECT 3"), Some("three"));
+        cache.resize(1);
+        assert_eq!(cache.get("SELECT 1"), None);
+        assert_eq!(cache.get("SELECT 3"), Some("three"));
+    }
+
+    #[test]
+    fn disabled_cache() {
+        let mut cache: StatementCache<&str> = StatementCache::new(0);
+        cache.insert("SELECT 1", "one");
+        assert!(cache.is_empty());
+        assert_eq!(cache.get("SELECT 1"), None);
+    }
+}
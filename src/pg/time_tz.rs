@@ -0,0 +1,54 @@
+//! `time with time zone` is stored as a 64 bit integer of the microseconds since midnight
+//! followed by a 32 bit integer of the zone offset in seconds west of utc.
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use chrono::{NaiveTime, Timelike};
+use postgres::types::{self, private::BytesMut, FromSql, IsNull, ToSql, Type};
+use std::convert::TryFrom;
+use std::error::Error;
+use wumn_dao::TimeTz;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PgTimeTz(pub TimeTz);
+
+impl<'a> FromSql<'a> for PgTimeTz {
+    fn from_sql(_ty: &Type, bytes: &'a [u8]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut bytes = <&[u8]>::clone(&bytes);
+        let microseconds = bytes.read_i64::<BigEndian>()?;
+        let zone = bytes.read_i32::<BigEndian>()?;
+        let seconds = u32::try_from(microseconds.div_euclid(1_000_000))?;
+        let nanos = u32::try_from(microseconds.rem_euclid(1_000_000) * 1_000)?;
+        let time = NaiveTime::from_num_seconds_from_midnight_opt(seconds, nanos)
+            .ok_or("time with time zone is out of range")?;
+        Ok(PgTimeTz(TimeTz {
+            time,
+            offset: -zone,
+        }))
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, types::Type::TIMETZ)
+    }
+}
+
+impl ToSql for PgTimeTz {
+    fn to_sql(
+        &self,
+        _ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        let time = self.0.time;
+        let microseconds = i64::from(time.num_seconds_from_midnight()) * 1_000_000
+            + i64::from(time.nanosecond() / 1_000);
+        let mut bytes = Vec::with_capacity(12);
+        bytes.write_i64::<BigEndian>(microseconds)?;
+        bytes.write_i32::<BigEndian>(-self.0.offset)?;
+        out.extend_from_slice(&bytes);
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, types::Type::TIMETZ)
+    }
+
+    to_sql_checked!();
+}
@@ -1,6 +1,7 @@
 use cfg_if::cfg_if;
 use crate::{
     error::ParseError,
+    mock::MockDB,
     Database,
 };
 //use log::*;
@@ -18,6 +19,8 @@ pub enum DBPlatform {
     #[cfg(feature = "with-postgres")]
     //Postgres(PostgresDB),
     Postgres(Box<PostgresDB>),
+    /// in-memory database returning canned results, for testing
+    Mock(Box<MockDB>),
 }
 
 impl Deref for DBPlatform {
@@ -27,6 +30,7 @@ impl Deref for DBPlatform {
         match *self {
             #[cfg(feature = "with-postgres")]
             DBPlatform::Postgres(ref pg) => pg.deref(),
+            DBPlatform::Mock(ref mock) => mock.deref(),
         }
     }
 }
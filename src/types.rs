@@ -1,5 +1,7 @@
+use std::ops::Bound;
 use wumn_dao::{
     value::Array,
+    Range,
     Value,
 };
 use serde::{
@@ -44,12 +46,30 @@ pub enum SqlType {
     Interval,
 
     IpAddress,
+    Cidr,
+    MacAddr,
 
     Point,
+    Line,
+    LineSegment,
+    Box,
+    Path,
+    Polygon,
+    Circle,
+    /// the type of the geometries and their spatial reference, ie: `geometry(Point,4326)`
+    Geometry(Option<String>, Option<i32>),
+    Geography(Option<String>, Option<i32>),
 
     // enum list with the choices value
     Enum(String, Vec<String>),
     Array(Box<SqlType>),
+    /// a range and a multirange of the element type, ie: `tstzrange`
+    Range(Box<SqlType>),
+    Multirange(Box<SqlType>),
+    /// a composite type of this name
+    Composite(String),
+    /// a domain of this name over its base type
+    Domain(String, Box<SqlType>),
 }
 
 impl SqlType {
@@ -102,6 +122,39 @@ impl SqlType {
     }
 }
 
+#[cfg(feature = "with-arrow")]
+impl SqlType {
+    /// the arrow type of the values of this column, used to type the record batch of the rows
+    /// selected from a table with `Rows::to_record_batch_with_types`.
+    /// Numeric is a text to keep its precision, a time with timezone is a text to keep its offset
+    /// the same way as `arrow_type` of its values, the unknown types are text.
+    pub fn to_arrow_type(&self) -> arrow_schema::DataType {
+        use arrow_schema::{DataType, IntervalUnit, TimeUnit};
+        match *self {
+            SqlType::Bool => DataType::Boolean,
+            SqlType::Tinyint => DataType::Int8,
+            SqlType::Smallint => DataType::Int16,
+            SqlType::Int => DataType::Int32,
+            SqlType::Bigint => DataType::Int64,
+            SqlType::Real | SqlType::Float => DataType::Float32,
+            SqlType::Double => DataType::Float64,
+            SqlType::Tinyblob
+            | SqlType::Mediumblob
+            | SqlType::Blob
+            | SqlType::Longblob
+            | SqlType::Varbinary => DataType::Binary,
+            SqlType::Date => DataType::Date32,
+            SqlType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+            SqlType::TimestampTz => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            SqlType::Time => DataType::Time64(TimeUnit::Microsecond),
+            SqlType::Interval => DataType::Interval(IntervalUnit::MonthDayNano),
+            SqlType::Array(ref ty) => wumn_dao::list_type(ty.to_arrow_type()),
+            SqlType::Domain(_, ref base) => base.to_arrow_type(),
+            _ => DataType::Utf8,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, PartialEq, Clone)]
 pub enum ArrayType {
     Bool,
@@ -153,10 +206,31 @@ impl HasType for Value {
             Value::Uuid(_) => Some(SqlType::Uuid),
             Value::Date(_) => Some(SqlType::Date),
             Value::Time(_) => Some(SqlType::Time),
+            Value::TimeTz(_) => Some(SqlType::TimeTz),
             Value::DateTime(_) => Some(SqlType::Timestamp),
-            Value::Timestamp(_) => Some(SqlType::Timestamp),
+            Value::Timestamp(_) => Some(SqlType::TimestampTz),
             Value::Interval(_) => Some(SqlType::Interval),
+            Value::Inet(_) => Some(SqlType::IpAddress),
+            Value::Cidr(_) => Some(SqlType::Cidr),
+            Value::MacAddr(_) => Some(SqlType::MacAddr),
             Value::Point(_) => Some(SqlType::Point),
+            Value::Line(_) => Some(SqlType::Line),
+            Value::LineSegment(_) => Some(SqlType::LineSegment),
+            Value::Box(_) => Some(SqlType::Box),
+            Value::Path(_) => Some(SqlType::Path),
+            Value::Polygon(_) => Some(SqlType::Polygon),
+            Value::Circle(_) => Some(SqlType::Circle),
+            Value::Geometry(ref v) => {
+                Some(SqlType::Geometry(Some(v.type_name().to_string()), v.srid))
+            }
+            // the element type is known from the bounds
+            Value::Range(ref v) => range_type(v).map(|ty| SqlType::Range(Box::new(ty))),
+            Value::Multirange(ref v) => v
+                .iter()
+                .find_map(range_type)
+                .map(|ty| SqlType::Multirange(Box::new(ty))),
+            // the name of the composite type is not known from the value
+            Value::Composite(_) => None,
             Value::Array(Array::Int(_)) => {
                 Some(SqlType::Array(Box::new(SqlType::Int)))
             }
@@ -170,15 +244,34 @@ impl HasType for Value {
     }
 }
 
+fn range_type(range: &Range<Value>) -> Option<SqlType> {
+    [&range.lower, &range.upper].iter().find_map(|bound| match **bound {
+        Bound::Included(ref v) | Bound::Excluded(ref v) => v.get_type(),
+        Bound::Unbounded => None,
+    })
+}
+
 impl SqlType {
     pub fn same_type(&self, value: &Value) -> bool {
+        match (self, value) {
+            (SqlType::Domain(_, base), _) => return base.same_type(value),
+            (SqlType::Composite(_), Value::Composite(_)) => return true,
+            (_, _) => (),
+        }
         if let Some(simple_type) = value.get_type() {
             if simple_type == *self{
                 return true
             }
             match (self, value){
                 (SqlType::Varchar, Value::Text(_)) => true,
-                (SqlType::TimestampTz, Value::Timestamp(_)) => true,
+                (SqlType::Timestamp, Value::Timestamp(_)) => true,
+                (SqlType::Blob, Value::ImageUri(_)) => true,
+                (SqlType::IpAddress, Value::Cidr(_)) => true,
+                (SqlType::Cidr, Value::Inet(_)) => true,
+                (SqlType::Geometry(_, _), Value::Geometry(_)) => true,
+                (SqlType::Geography(_, _), Value::Geometry(_)) => true,
+                (SqlType::Range(_), Value::Range(_)) => true,
+                (SqlType::Multirange(_), Value::Multirange(_)) => true,
                 (_, _) => false
             }
         } else {
@@ -8,24 +8,47 @@ use serde::{
     Serialize,
 };
 
-#[allow(unused)]
 mod previlege;
 
-/// This is the user object mapped from pg_authid
+pub use previlege::{has_column_privilege, Privilege, UserPrivilege};
+
+/// This is the user object mapped from pg_authid, or pg_roles when pg_authid
+/// is not readable by the current user.
+/// It is also used as the specification of the role attributes when creating or altering roles
 #[derive(Debug, Serialize, Deserialize, FromDao)]
 pub struct User {
-    sysid: i32,
-    username: String,
-    password: String,
-    is_superuser: bool,
-    is_inherit: bool,
-    can_create_db: bool,
-    can_create_role: bool,
-    can_login: bool,
-    can_do_replication: bool,
-    can_bypass_rls: bool,
-    valid_until: Option<DateTime<Utc>>,
-    conn_limit: Option<i32>,
+    pub sysid: i32,
+    pub username: String,
+    pub password: String,
+    pub is_superuser: bool,
+    pub is_inherit: bool,
+    pub can_create_db: bool,
+    pub can_create_role: bool,
+    pub can_login: bool,
+    pub can_do_replication: bool,
+    pub can_bypass_rls: bool,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub conn_limit: Option<i32>,
+}
+
+impl User {
+    /// a login user with no special attributes, which inherits the privileges of its roles
+    pub fn new(username: &str) -> Self {
+        User {
+            sysid: 0,
+            username: username.to_string(),
+            password: String::new(),
+            is_superuser: false,
+            is_inherit: true,
+            can_create_db: false,
+            can_create_role: false,
+            can_login: true,
+            can_do_replication: false,
+            can_bypass_rls: false,
+            valid_until: None,
+            conn_limit: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, FromDao)]
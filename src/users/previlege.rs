@@ -1,13 +1,17 @@
 use crate::{
-    users::User,
     ColumnName,
     TableName,
 };
+use serde::{
+    Deserialize,
+    Serialize,
+};
 
 /// User can have previlege to tables, to columns
 /// The table models can be filtered depending on how much
 ///  and which columns it has privilege
-enum Privilege {
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub enum Privilege {
     Select,
     Insert,
     Update,
@@ -15,22 +19,132 @@ enum Privilege {
     Create,
     Drop,
     Truncate,
+    References,
+    Trigger,
     Connect,
     Execute,
 }
 
+impl Privilege {
+    /// parse the privilege_type as reported in information_schema grants
+    pub fn from_privilege_type(privilege_type: &str) -> Option<Self> {
+        match &*privilege_type.to_uppercase() {
+            "SELECT" => Some(Privilege::Select),
+            "INSERT" => Some(Privilege::Insert),
+            "UPDATE" => Some(Privilege::Update),
+            "DELETE" => Some(Privilege::Delete),
+            "CREATE" => Some(Privilege::Create),
+            "DROP" => Some(Privilege::Drop),
+            "TRUNCATE" => Some(Privilege::Truncate),
+            "REFERENCES" => Some(Privilege::References),
+            "TRIGGER" => Some(Privilege::Trigger),
+            "CONNECT" => Some(Privilege::Connect),
+            "EXECUTE" => Some(Privilege::Execute),
+            _ => None,
+        }
+    }
+
+    /// the keyword of this privilege used in GRANT and REVOKE
+    pub fn to_sql(&self) -> &'static str {
+        match *self {
+            Privilege::Select => "SELECT",
+            Privilege::Insert => "INSERT",
+            Privilege::Update => "UPDATE",
+            Privilege::Delete => "DELETE",
+            Privilege::Create => "CREATE",
+            Privilege::Drop => "DROP",
+            Privilege::Truncate => "TRUNCATE",
+            Privilege::References => "REFERENCES",
+            Privilege::Trigger => "TRIGGER",
+            Privilege::Connect => "CONNECT",
+            Privilege::Execute => "EXECUTE",
+        }
+    }
+}
+
+/// User privileges for each tables, derived from the grants in
+/// information_schema.role_table_grants and information_schema.column_privileges
 ///
-///  CREATE TABLE user_privilege(
-///     user_id int,
-///     schema text,
-///     table_name text,
-///     columns text[], -- if no column mentioned, then the user has priviledge to all of the table columns
-///     privilege text[],
-///  )
-/// User privileges for each tables
-struct UserPrivilege {
-    user: User,
-    table_name: TableName,
-    column_names: Vec<ColumnName>,
-    privilege: Vec<Privilege>,
+/// if no column is mentioned, then the user has privilege to all of the table columns
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct UserPrivilege {
+    pub username: String,
+    pub table_name: TableName,
+    pub column_names: Vec<ColumnName>,
+    pub privilege: Vec<Privilege>,
+}
+
+impl UserPrivilege {
+    /// check if this privilege is about this table
+    pub fn is_for_table(&self, table_name: &TableName) -> bool {
+        let schema = |t: &TableName| match t.schema {
+            Some(ref schema) => schema.to_string(),
+            None => "public".to_string(),
+        };
+        self.table_name.name == table_name.name && schema(&self.table_name) == schema(table_name)
+    }
+
+    /// check if this privilege covers the column, table wide privilege covers all the columns
+    pub fn is_for_column(&self, column_name: &ColumnName) -> bool {
+        self.column_names.is_empty() || self.column_names.iter().any(|c| c.name == column_name.name)
+    }
+
+    pub fn has_privilege(&self, privilege: &Privilege) -> bool {
+        self.privilege.contains(privilege)
+    }
+}
+
+/// check if any of the privileges allows the operation on this table column
+pub fn has_column_privilege(
+    privileges: &[UserPrivilege],
+    table_name: &TableName,
+    column_name: &ColumnName,
+    privilege: &Privilege,
+) -> bool {
+    privileges.iter().any(|p| {
+        p.is_for_table(table_name) && p.is_for_column(column_name) && p.has_privilege(privilege)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_privilege() {
+        let privileges = vec![
+            UserPrivilege {
+                username: "clerk".into(),
+                table_name: TableName::from("public.actor"),
+                column_names: vec![],
+                privilege: vec![Privilege::Select],
+            },
+            UserPrivilege {
+                username: "clerk".into(),
+                table_name: TableName::from("public.actor"),
+                column_names: vec![ColumnName::from("first_name")],
+                privilege: vec![Privilege::Update],
+            },
+        ];
+        let actor = TableName::from("actor");
+        let first_name = ColumnName::from("first_name");
+        let last_name = ColumnName::from("last_name");
+        assert!(has_column_privilege(&privileges, &actor, &first_name, &Privilege::Select));
+        assert!(has_column_privilege(&privileges, &actor, &last_name, &Privilege::Select));
+        assert!(has_column_privilege(&privileges, &actor, &first_name, &Privilege::Update));
+        assert!(!has_column_privilege(&privileges, &actor, &last_name, &Privilege::Update));
+        assert!(!has_column_privilege(
+            &privileges,
+            &TableName::from("film"),
+            &first_name,
+            &Privilege::Select
+        ));
+    }
+
+    #[test]
+    fn parse_privilege_type() {
+        assert_eq!(Privilege::from_privilege_type("SELECT"), Some(Privilege::Select));
+        assert_eq!(Privilege::from_privilege_type("TRIGGER"), Some(Privilege::Trigger));
+        assert_eq!(Privilege::from_privilege_type("MAINTAIN"), None);
+    }
 }